@@ -0,0 +1,635 @@
+//! A minimal JSON representation of the generic XDR values of the module `dynamic`
+//!
+//! - integers become JSON numbers, 64 bit integers become strings to avoid a loss of precision
+//! - opaque data becomes a hex string
+//! - strings become JSON strings, strings that are not valid UTF-8 become `{"hex": "..."}`
+//! - options become `null` or their value, arrays become JSON arrays
+//! - enum cases become their name as in the XDR definition
+//! - structs become objects with the fields in the order of the XDR definition
+//! - unions become objects with the discriminant under the name of the switch, e.g., `{"type": "MEMO_ID", "id": "1"}`
+
+use std::fmt::Write;
+
+use substrate_stellar_xdr::{
+    dynamic::{
+        self, discriminant_value, enum_value, SchemaDefinition, SchemaType, XdrValue,
+        UNKNOWN_ENUM_CASE,
+    },
+    xdr::schema_definition,
+    xdr_codec::{decode_hex, encode_hex},
+};
+
+/// A JSON value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Render this value as indented JSON text
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        self.render_into(&mut output, 0);
+        output
+    }
+
+    fn render_into(&self, output: &mut String, indentation: usize) {
+        match self {
+            Json::Null => output.push_str("null"),
+            Json::Bool(value) => write!(output, "{}", value).unwrap(),
+            Json::Number(value) => write!(output, "{}", value).unwrap(),
+            Json::String(value) => render_string(output, value),
+            Json::Array(values) if values.is_empty() => output.push_str("[]"),
+            Json::Array(values) => {
+                output.push_str("[\n");
+                for (index, value) in values.iter().enumerate() {
+                    push_indentation(output, indentation + 1);
+                    value.render_into(output, indentation + 1);
+                    output.push_str(if index + 1 < values.len() {
+                        ",\n"
+                    } else {
+                        "\n"
+                    });
+                }
+                push_indentation(output, indentation);
+                output.push(']');
+            }
+            Json::Object(entries) if entries.is_empty() => output.push_str("{}"),
+            Json::Object(entries) => {
+                output.push_str("{\n");
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    push_indentation(output, indentation + 1);
+                    render_string(output, key);
+                    output.push_str(": ");
+                    value.render_into(output, indentation + 1);
+                    output.push_str(if index + 1 < entries.len() {
+                        ",\n"
+                    } else {
+                        "\n"
+                    });
+                }
+                push_indentation(output, indentation);
+                output.push('}');
+            }
+        }
+    }
+
+    /// Parse JSON text
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = Parser {
+            text: text.as_bytes(),
+            position: 0,
+        };
+        let value = parser.parse_value(0)?;
+        parser.skip_whitespace();
+        if parser.position != parser.text.len() {
+            return Err(parser.error("unexpected text after the JSON value"));
+        }
+        Ok(value)
+    }
+}
+
+fn push_indentation(output: &mut String, indentation: usize) {
+    for _ in 0..indentation {
+        output.push_str("  ");
+    }
+}
+
+fn render_string(output: &mut String, value: &str) {
+    output.push('"');
+    for character in value.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                write!(output, "\\u{:04x}", character as u32).unwrap()
+            }
+            character => output.push(character),
+        }
+    }
+    output.push('"');
+}
+
+/// The maximal number of nested JSON arrays and objects that the parser descends into
+///
+/// Like the decoder of the module `dynamic`, the parser is recursive, so that deeply nested input
+/// could otherwise overflow the stack. Every definition that the decoder descends into adds at most
+/// two levels, e.g., a struct with an array field, so the JSON of every decodable value is accepted.
+const MAX_DEPTH: usize = 2 * dynamic::MAX_DEPTH;
+
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> String {
+        format!("invalid JSON at byte {}: {}", self.position, message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.position < self.text.len() && self.text[self.position].is_ascii_whitespace() {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.text.get(self.position).copied()
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), String> {
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("expected `{}`", expected as char)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn parse_keyword(&mut self, keyword: &str, value: Json) -> Result<Json, String> {
+        if !self.text[self.position..].starts_with(keyword.as_bytes()) {
+            return Err(self.error("unexpected character"));
+        }
+        self.position += keyword.len();
+        Ok(value)
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<Json, String> {
+        let next = self.peek();
+        if matches!(next, Some(b'[') | Some(b'{')) && depth >= MAX_DEPTH {
+            return Err(self.error("the value is nested too deeply"));
+        }
+
+        match next {
+            Some(b'n') => self.parse_keyword("null", Json::Null),
+            Some(b't') => self.parse_keyword("true", Json::Bool(true)),
+            Some(b'f') => self.parse_keyword("false", Json::Bool(false)),
+            Some(b'"') => Ok(Json::String(self.parse_string()?)),
+            Some(b'[') => {
+                self.position += 1;
+                let mut values = Vec::new();
+                if self.peek() == Some(b']') {
+                    self.position += 1;
+                    return Ok(Json::Array(values));
+                }
+                loop {
+                    values.push(self.parse_value(depth + 1)?);
+                    match self.peek() {
+                        Some(b',') => self.position += 1,
+                        Some(b']') => {
+                            self.position += 1;
+                            return Ok(Json::Array(values));
+                        }
+                        _ => return Err(self.error("expected `,` or `]`")),
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.position += 1;
+                let mut entries = Vec::new();
+                if self.peek() == Some(b'}') {
+                    self.position += 1;
+                    return Ok(Json::Object(entries));
+                }
+                loop {
+                    if self.peek() != Some(b'"') {
+                        return Err(self.error("expected a key"));
+                    }
+                    let key = self.parse_string()?;
+                    self.expect(b':')?;
+                    entries.push((key, self.parse_value(depth + 1)?));
+                    match self.peek() {
+                        Some(b',') => self.position += 1,
+                        Some(b'}') => {
+                            self.position += 1;
+                            return Ok(Json::Object(entries));
+                        }
+                        _ => return Err(self.error("expected `,` or `}`")),
+                    }
+                }
+            }
+            Some(b'-') | Some(b'0'..=b'9') => {
+                let start = self.position;
+                self.position += 1;
+                while self.position < self.text.len() && self.text[self.position].is_ascii_digit() {
+                    self.position += 1;
+                }
+                let number = std::str::from_utf8(&self.text[start..self.position]).unwrap();
+                number
+                    .parse()
+                    .map(Json::Number)
+                    .map_err(|_| self.error("only integers are supported"))
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn parse_hex_escape(&mut self) -> Result<u32, String> {
+        let digits = self
+            .text
+            .get(self.position..self.position + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.position += 4;
+        Ok(digits)
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut result = Vec::new();
+        loop {
+            let byte = *self
+                .text
+                .get(self.position)
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.position += 1;
+            match byte {
+                b'"' => return String::from_utf8(result).map_err(|_| self.error("invalid UTF-8")),
+                b'\\' => {
+                    let escaped = *self
+                        .text
+                        .get(self.position)
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.position += 1;
+                    let character = match escaped {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code_point = self.parse_hex_escape()?;
+                            if (0xd800..0xdc00).contains(&code_point)
+                                && self.text[self.position..].starts_with(b"\\u")
+                            {
+                                self.position += 2;
+                                let low = self.parse_hex_escape()?;
+                                code_point = 0x10000
+                                    + ((code_point - 0xd800) << 10)
+                                    + (low.wrapping_sub(0xdc00) & 0x3ff);
+                            }
+                            std::char::from_u32(code_point)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    let mut buffer = [0; 4];
+                    result.extend_from_slice(character.encode_utf8(&mut buffer).as_bytes());
+                }
+                byte => result.push(byte),
+            }
+        }
+    }
+}
+
+fn lookup(type_name: &str) -> Result<&'static SchemaDefinition, String> {
+    schema_definition(type_name).ok_or_else(|| format!("unknown type {}", type_name))
+}
+
+/// Convert a generic value of the XDR type with the given name to JSON
+pub fn to_json(type_name: &str, value: &XdrValue) -> Result<Json, String> {
+    reference_to_json(type_name, value, "")
+}
+
+fn mismatch(path: &str) -> String {
+    format!("the value at `{}` does not match the schema", path)
+}
+
+fn value_to_json(schema_type: &SchemaType, value: &XdrValue, path: &str) -> Result<Json, String> {
+    Ok(match (schema_type, value) {
+        (SchemaType::Int, XdrValue::Int(value)) => Json::Number(*value as i64),
+        (SchemaType::UnsignedInt, XdrValue::UnsignedInt(value)) => Json::Number(*value as i64),
+        (SchemaType::Hyper, XdrValue::Hyper(value)) => Json::String(value.to_string()),
+        (SchemaType::UnsignedHyper, XdrValue::UnsignedHyper(value)) => {
+            Json::String(value.to_string())
+        }
+        (SchemaType::Bool, XdrValue::Bool(value)) => Json::Bool(*value),
+        (SchemaType::Opaque(_), XdrValue::Opaque(bytes))
+        | (SchemaType::VarOpaque(_), XdrValue::Opaque(bytes)) => Json::String(encode_hex(bytes)),
+        (SchemaType::String(_), XdrValue::String(bytes)) => match std::str::from_utf8(bytes) {
+            Ok(text) => Json::String(text.to_string()),
+            Err(_) => Json::Object(vec![("hex".to_string(), Json::String(encode_hex(bytes)))]),
+        },
+        (SchemaType::Array(inner_type, _), XdrValue::Array(values))
+        | (SchemaType::VarArray(inner_type, _), XdrValue::Array(values)) => Json::Array(
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    value_to_json(inner_type, value, &format!("{}[{}]", path, index))
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        (SchemaType::Optional(_), XdrValue::Optional(None)) => Json::Null,
+        (SchemaType::Optional(inner_type), XdrValue::Optional(Some(value))) => {
+            value_to_json(inner_type, value, path)?
+        }
+        (SchemaType::Reference(type_name), value) => reference_to_json(type_name, value, path)?,
+        _ => return Err(mismatch(path)),
+    })
+}
+
+fn join_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn reference_to_json(type_name: &str, value: &XdrValue, path: &str) -> Result<Json, String> {
+    Ok(match (lookup(type_name)?, value) {
+        (SchemaDefinition::Typedef(schema_type), value) => value_to_json(schema_type, value, path)?,
+        (SchemaDefinition::Enum { .. }, XdrValue::Enum { name, value }) => {
+            if *name == UNKNOWN_ENUM_CASE {
+                Json::String(value.to_string())
+            } else {
+                Json::String(name.to_string())
+            }
+        }
+        (SchemaDefinition::Struct(fields), XdrValue::Struct(values))
+            if fields.len() == values.len() =>
+        {
+            Json::Object(
+                fields
+                    .iter()
+                    .zip(values.iter())
+                    .map(|((name, schema_type), (_, value))| {
+                        let field_path = join_path(path, name);
+                        Ok((
+                            name.to_string(),
+                            value_to_json(schema_type, value, &field_path)?,
+                        ))
+                    })
+                    .collect::<Result<_, String>>()?,
+            )
+        }
+        (
+            SchemaDefinition::Union {
+                switch_name,
+                switch,
+                arms,
+                ..
+            },
+            XdrValue::Union { discriminant, arm },
+        ) => {
+            let mut entries = vec![(
+                switch_name.to_string(),
+                value_to_json(switch, discriminant, &join_path(path, switch_name))?,
+            )];
+            if let Some((arm_name, value)) = arm {
+                let arm_type = arms
+                    .iter()
+                    .find_map(|arm| match arm.arm {
+                        Some((name, arm_type)) if name == *arm_name => Some(arm_type),
+                        _ => None,
+                    })
+                    .ok_or_else(|| mismatch(path))?;
+                let arm_path = join_path(path, arm_name);
+                entries.push((
+                    arm_name.to_string(),
+                    value_to_json(&arm_type, value, &arm_path)?,
+                ));
+            }
+            Json::Object(entries)
+        }
+        _ => return Err(mismatch(path)),
+    })
+}
+
+/// Convert JSON to a generic value of the XDR type with the given name
+pub fn from_json(type_name: &str, json: &Json) -> Result<XdrValue, String> {
+    reference_from_json(type_name, json, "")
+}
+
+fn expected(path: &str, description: &str) -> String {
+    format!(
+        "expected {} at `{}`",
+        description,
+        if path.is_empty() { "." } else { path }
+    )
+}
+
+fn integer_from_json<T: std::str::FromStr + std::convert::TryFrom<i64>>(
+    json: &Json,
+    path: &str,
+) -> Result<T, String> {
+    match json {
+        Json::Number(value) => T::try_from(*value).ok(),
+        Json::String(value) => value.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| expected(path, "an integer"))
+}
+
+fn value_from_json(schema_type: &SchemaType, json: &Json, path: &str) -> Result<XdrValue, String> {
+    Ok(match (schema_type, json) {
+        (SchemaType::Int, json) => XdrValue::Int(integer_from_json(json, path)?),
+        (SchemaType::UnsignedInt, json) => XdrValue::UnsignedInt(integer_from_json(json, path)?),
+        (SchemaType::Hyper, json) => XdrValue::Hyper(integer_from_json(json, path)?),
+        (SchemaType::UnsignedHyper, json) => {
+            XdrValue::UnsignedHyper(integer_from_json(json, path)?)
+        }
+        (SchemaType::Bool, Json::Bool(value)) => XdrValue::Bool(*value),
+        (SchemaType::Opaque(_), Json::String(text))
+        | (SchemaType::VarOpaque(_), Json::String(text)) => XdrValue::Opaque(
+            decode_hex(text.as_bytes()).ok_or_else(|| expected(path, "a hex string"))?,
+        ),
+        (SchemaType::String(_), Json::String(text)) => XdrValue::String(text.as_bytes().to_vec()),
+        (SchemaType::String(_), json @ Json::Object(_)) => match json.get("hex") {
+            Some(Json::String(text)) => XdrValue::String(
+                decode_hex(text.as_bytes()).ok_or_else(|| expected(path, "a hex string"))?,
+            ),
+            _ => return Err(expected(path, "a string")),
+        },
+        (SchemaType::Array(inner_type, _), Json::Array(values))
+        | (SchemaType::VarArray(inner_type, _), Json::Array(values)) => XdrValue::Array(
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    value_from_json(inner_type, value, &format!("{}[{}]", path, index))
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        (SchemaType::Optional(_), Json::Null) => XdrValue::Optional(None),
+        (SchemaType::Optional(inner_type), json) => {
+            XdrValue::Optional(Some(Box::new(value_from_json(inner_type, json, path)?)))
+        }
+        (SchemaType::Reference(type_name), json) => reference_from_json(type_name, json, path)?,
+        (SchemaType::Bool, _) => return Err(expected(path, "a boolean")),
+        (SchemaType::Array(..), _) | (SchemaType::VarArray(..), _) => {
+            return Err(expected(path, "an array"))
+        }
+        _ => return Err(expected(path, "a string")),
+    })
+}
+
+fn reference_from_json(type_name: &str, json: &Json, path: &str) -> Result<XdrValue, String> {
+    match lookup(type_name)? {
+        SchemaDefinition::Typedef(schema_type) => value_from_json(schema_type, json, path),
+        SchemaDefinition::Enum { cases, open } => {
+            let name = match json {
+                Json::String(name) => name,
+                _ => return Err(expected(path, "the name of an enum case")),
+            };
+            match cases.iter().find(|case| case.name == name) {
+                Some(case) => Ok(XdrValue::Enum {
+                    name: case.name,
+                    value: case.value,
+                }),
+                // unknown values of open enums are represented by their integer value
+                None => name
+                    .parse::<i32>()
+                    .ok()
+                    .and_then(|value| enum_value(cases, *open, value))
+                    .ok_or_else(|| {
+                        format!(
+                            "unknown case {} of the enum {} at `{}`",
+                            name, type_name, path
+                        )
+                    }),
+            }
+        }
+        SchemaDefinition::Struct(fields) => {
+            let entries = match json {
+                Json::Object(entries) => entries,
+                _ => return Err(expected(path, "an object")),
+            };
+            if let Some((key, _)) = entries
+                .iter()
+                .find(|(key, _)| !fields.iter().any(|(name, _)| name == key))
+            {
+                return Err(format!(
+                    "unknown field {} of the struct {} at `{}`",
+                    key, type_name, path
+                ));
+            }
+
+            let mut values = Vec::with_capacity(fields.len());
+            for (name, schema_type) in fields.iter() {
+                let field_path = join_path(path, name);
+                let field = json
+                    .get(name)
+                    .ok_or_else(|| format!("missing field `{}`", field_path))?;
+                values.push((*name, value_from_json(schema_type, field, &field_path)?));
+            }
+            Ok(XdrValue::Struct(values))
+        }
+        SchemaDefinition::Union {
+            switch_name,
+            switch,
+            arms,
+            has_default_arm,
+        } => {
+            let switch_path = join_path(path, switch_name);
+            let discriminant_json = json
+                .get(switch_name)
+                .ok_or_else(|| format!("missing discriminant `{}`", switch_path))?;
+            let discriminant = value_from_json(switch, discriminant_json, &switch_path)?;
+            let discriminant_number = discriminant_value(&discriminant)
+                .ok_or_else(|| expected(&switch_path, "a discriminant"))?;
+
+            let arm = match arms
+                .iter()
+                .find(|arm| arm.discriminant == discriminant_number)
+            {
+                Some(schema_arm) => schema_arm.arm,
+                None if *has_default_arm => None,
+                None => return Err(format!("invalid discriminant at `{}`", switch_path)),
+            };
+            let arm = match arm {
+                Some((arm_name, arm_type)) => {
+                    let arm_path = join_path(path, arm_name);
+                    let arm_json = json
+                        .get(arm_name)
+                        .ok_or_else(|| format!("missing union arm `{}`", arm_path))?;
+                    Some((
+                        arm_name,
+                        Box::new(value_from_json(&arm_type, arm_json, &arm_path)?),
+                    ))
+                }
+                None => None,
+            };
+
+            let no_of_keys = match json {
+                Json::Object(entries) => entries.len(),
+                _ => 0,
+            };
+            if no_of_keys != 1 + arm.is_some() as usize {
+                return Err(format!(
+                    "unexpected keys in the union {} at `{}`",
+                    type_name, path
+                ));
+            }
+
+            Ok(XdrValue::Union {
+                discriminant: Box::new(discriminant),
+                arm,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use substrate_stellar_xdr::dynamic::{decode_dynamic, encode_dynamic};
+
+    #[test]
+    fn parse_and_render() {
+        let text = "{\n  \"a\": [\n    1,\n    -2\n  ],\n  \"b\": \"x\\\"\\u00e9\\n\",\n  \"c\": null,\n  \"d\": {}\n}";
+        let json = Json::parse(text).unwrap();
+        assert_eq!(
+            json,
+            Json::Object(vec![
+                (
+                    "a".to_string(),
+                    Json::Array(vec![Json::Number(1), Json::Number(-2)])
+                ),
+                ("b".to_string(), Json::String("x\"é\n".to_string())),
+                ("c".to_string(), Json::Null),
+                ("d".to_string(), Json::Object(vec![])),
+            ])
+        );
+        assert_eq!(json.render(), text.replace("\\u00e9", "é"));
+        assert!(Json::parse("[1, 2.5]").is_err());
+        assert!(Json::parse("{\"a\": 1} x").is_err());
+    }
+
+    #[test]
+    fn memo_round_trip() {
+        let xdr = [0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 7];
+        let value = decode_dynamic("Memo", &xdr[..]).unwrap();
+        let json = to_json("Memo", &value).unwrap();
+        assert_eq!(
+            json.render(),
+            "{\n  \"type\": \"MEMO_ID\",\n  \"id\": \"7\"\n}"
+        );
+
+        let parsed = from_json("Memo", &Json::parse(&json.render()).unwrap()).unwrap();
+        assert_eq!(encode_dynamic("Memo", &parsed).unwrap(), xdr);
+    }
+}
@@ -0,0 +1,271 @@
+//! Command-line tool to decode, encode and convert Stellar XDR
+
+mod json;
+
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    process,
+};
+
+use substrate_stellar_xdr::{
+    dynamic::{decode_dynamic, encode_dynamic, guess_types},
+    txrep::{parse_txrep, render_txrep},
+    xdr,
+    xdr_codec::{decode_hex, encode_hex},
+};
+
+use json::{from_json, to_json, Json};
+
+const USAGE: &str = "Usage: stellar-xdr <COMMAND> [OPTIONS] [FILE]
+
+Reads from FILE or, if FILE is missing or `-`, from the standard input.
+
+Commands:
+  decode --type <TYPE> [--input base64|hex|raw] [--output json|txrep|debug]
+      Decode XDR of the given type (default: base64 to json)
+  encode --type <TYPE> [--input json|txrep] [--output base64|hex|raw]
+      Encode a value of the given type as XDR (default: json to base64)
+  guess [--input base64|hex|raw]
+      List the types that the XDR can be decoded as, most plausible first
+  convert --from base64|hex|raw --to base64|hex|raw
+      Convert the transport encoding of XDR
+  types
+      List the names and kinds of all XDR types
+  help
+      Show this help";
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Encoding {
+    Base64,
+    Hex,
+    Raw,
+}
+
+impl Encoding {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "base64" => Ok(Encoding::Base64),
+            "hex" => Ok(Encoding::Hex),
+            "raw" => Ok(Encoding::Raw),
+            _ => Err(format!(
+                "unknown encoding {}, expected base64, hex or raw",
+                name
+            )),
+        }
+    }
+
+    fn decode(self, input: Vec<u8>) -> Result<Vec<u8>, String> {
+        let text = || {
+            String::from_utf8(input.clone())
+                .map(|text| text.split_whitespace().collect::<String>())
+                .map_err(|_| "the input is not valid UTF-8".to_string())
+        };
+
+        match self {
+            Encoding::Base64 => {
+                base64::decode(text()?).map_err(|error| format!("invalid base64: {}", error))
+            }
+            Encoding::Hex => {
+                decode_hex(text()?.as_bytes()).ok_or_else(|| "invalid hex".to_string())
+            }
+            Encoding::Raw => Ok(input),
+        }
+    }
+
+    fn encode(self, xdr: &[u8]) -> Vec<u8> {
+        match self {
+            Encoding::Base64 => format!("{}\n", base64::encode(xdr)).into_bytes(),
+            Encoding::Hex => format!("{}\n", encode_hex(xdr)).into_bytes(),
+            Encoding::Raw => xdr.to_vec(),
+        }
+    }
+}
+
+/// The options and the file of a command line
+struct Arguments {
+    options: Vec<(String, String)>,
+    file: Option<String>,
+}
+
+impl Arguments {
+    fn parse(arguments: &[String], allowed_options: &[&str]) -> Result<Self, String> {
+        let mut options = Vec::new();
+        let mut file = None;
+        let mut arguments = arguments.iter();
+
+        while let Some(argument) = arguments.next() {
+            if let Some(name) = argument.strip_prefix("--") {
+                if !allowed_options.contains(&name) {
+                    return Err(format!("unknown option --{}", name));
+                }
+                let value = arguments
+                    .next()
+                    .ok_or_else(|| format!("the option --{} requires a value", name))?;
+                options.push((name.to_string(), value.clone()));
+            } else if file.is_none() {
+                file = Some(argument.clone());
+            } else {
+                return Err(format!("unexpected argument {}", argument));
+            }
+        }
+
+        Ok(Arguments { options, file })
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(option, _)| option == name)
+            .map(|(_, value)| &value[..])
+    }
+
+    fn required_option(&self, name: &str) -> Result<&str, String> {
+        self.option(name)
+            .ok_or_else(|| format!("the option --{} is required", name))
+    }
+
+    fn read_input(&self) -> Result<Vec<u8>, String> {
+        match self.file.as_deref() {
+            None | Some("-") => {
+                let mut input = Vec::new();
+                io::stdin()
+                    .read_to_end(&mut input)
+                    .map_err(|error| format!("cannot read the standard input: {}", error))?;
+                Ok(input)
+            }
+            Some(file) => {
+                fs::read(file).map_err(|error| format!("cannot read {}: {}", file, error))
+            }
+        }
+    }
+}
+
+fn check_type(type_name: &str) -> Result<(), String> {
+    match xdr::schema_definition(type_name) {
+        Some(_) => Ok(()),
+        None => Err(format!(
+            "unknown type {}, run `stellar-xdr types` to list all types",
+            type_name
+        )),
+    }
+}
+
+fn decode(arguments: &Arguments) -> Result<Vec<u8>, String> {
+    let type_name = arguments.required_option("type")?;
+    check_type(type_name)?;
+    let input = Encoding::parse(arguments.option("input").unwrap_or("base64"))?;
+
+    let xdr = input.decode(arguments.read_input()?)?;
+    let value = decode_dynamic(type_name, &xdr)
+        .map_err(|error| format!("cannot decode {}: {:?}", type_name, error))?;
+
+    let output = match arguments.option("output").unwrap_or("json") {
+        "json" => to_json(type_name, &value)?.render() + "\n",
+        "txrep" => render_txrep(type_name, &value).map_err(|error| error.to_string())?,
+        "debug" => format!("{:#?}\n", value),
+        format => {
+            return Err(format!(
+                "unknown output format {}, expected json, txrep or debug",
+                format
+            ))
+        }
+    };
+    Ok(output.into_bytes())
+}
+
+fn encode(arguments: &Arguments) -> Result<Vec<u8>, String> {
+    let type_name = arguments.required_option("type")?;
+    check_type(type_name)?;
+    let output = Encoding::parse(arguments.option("output").unwrap_or("base64"))?;
+
+    let input = String::from_utf8(arguments.read_input()?)
+        .map_err(|_| "the input is not valid UTF-8".to_string())?;
+    let value = match arguments.option("input").unwrap_or("json") {
+        "json" => from_json(type_name, &Json::parse(&input)?)?,
+        "txrep" => parse_txrep(type_name, &input).map_err(|error| error.to_string())?,
+        format => {
+            return Err(format!(
+                "unknown input format {}, expected json or txrep",
+                format
+            ))
+        }
+    };
+
+    let xdr = encode_dynamic(type_name, &value)
+        .map_err(|error| format!("cannot encode {}: {:?}", type_name, error))?;
+    Ok(output.encode(&xdr))
+}
+
+fn guess(arguments: &Arguments) -> Result<Vec<u8>, String> {
+    let input = Encoding::parse(arguments.option("input").unwrap_or("base64"))?;
+
+    let xdr = input.decode(arguments.read_input()?)?;
+    let guesses = guess_types(&xdr);
+    if guesses.is_empty() {
+        return Err("the input cannot be decoded as any type".to_string());
+    }
+
+    Ok(guesses
+        .iter()
+        .map(|guess| format!("{}\n", guess.type_name))
+        .collect::<String>()
+        .into_bytes())
+}
+
+fn convert(arguments: &Arguments) -> Result<Vec<u8>, String> {
+    let from = Encoding::parse(arguments.required_option("from")?)?;
+    let to = Encoding::parse(arguments.required_option("to")?)?;
+
+    let xdr = from.decode(arguments.read_input()?)?;
+    Ok(to.encode(&xdr))
+}
+
+fn list_types() -> Vec<u8> {
+    let mut metadata = xdr::all_type_metadata();
+    metadata.sort_by_key(|metadata| metadata.name);
+
+    metadata
+        .iter()
+        .map(|metadata| format!("{} ({:?})\n", metadata.name, metadata.kind))
+        .collect::<String>()
+        .into_bytes()
+}
+
+fn run(arguments: &[String]) -> Result<Vec<u8>, String> {
+    let (command, arguments) = match arguments.split_first() {
+        Some((command, arguments)) => (&command[..], arguments),
+        None => return Err(USAGE.to_string()),
+    };
+
+    match command {
+        "decode" => decode(&Arguments::parse(arguments, &["type", "input", "output"])?),
+        "encode" => encode(&Arguments::parse(arguments, &["type", "input", "output"])?),
+        "guess" => guess(&Arguments::parse(arguments, &["input"])?),
+        "convert" => convert(&Arguments::parse(arguments, &["from", "to"])?),
+        "types" => {
+            Arguments::parse(arguments, &[])?;
+            Ok(list_types())
+        }
+        "help" | "--help" | "-h" => Ok(format!("{}\n", USAGE).into_bytes()),
+        command => Err(format!("unknown command {}\n\n{}", command, USAGE)),
+    }
+}
+
+fn main() {
+    let arguments: Vec<String> = env::args().skip(1).collect();
+
+    match run(&arguments) {
+        Ok(output) => {
+            if let Err(error) = io::stdout().write_all(&output) {
+                eprintln!("error: cannot write the output: {}", error);
+                process::exit(1);
+            }
+        }
+        Err(error) => {
+            eprintln!("error: {}", error);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Runs the commands of the command-line tool on the golden vectors of the folder `test/corpus`
+
+use std::{
+    ffi::OsStr,
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// A single golden vector of the corpus
+struct CorpusEntry {
+    location: String,
+    type_name: String,
+    base64_xdr: String,
+}
+
+fn read_corpus() -> Vec<CorpusEntry> {
+    let corpus_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../test/corpus");
+    let mut file_paths: Vec<_> = fs::read_dir(&corpus_path)
+        .expect("Cannot read the corpus folder")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some(OsStr::new("txt")))
+        .collect();
+    file_paths.sort();
+
+    let mut entries = Vec::new();
+    for file_path in file_paths {
+        let content = fs::read_to_string(&file_path).unwrap();
+        for (line_index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            entries.push(CorpusEntry {
+                location: format!("{}:{}", file_path.display(), line_index + 1),
+                type_name: parts.next().unwrap().to_owned(),
+                base64_xdr: parts.next().unwrap().to_owned(),
+            });
+        }
+    }
+
+    assert!(!entries.is_empty(), "the corpus is empty");
+    entries
+}
+
+/// Run the command-line tool with the given arguments and standard input, returns the standard
+/// output if the tool succeeds and the standard error otherwise
+fn run(arguments: &[&str], input: &[u8]) -> Result<Vec<u8>, String> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_stellar-xdr"))
+        .args(arguments)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Cannot start stellar-xdr");
+    child.stdin.take().unwrap().write_all(input).unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    match output.status.success() {
+        true => Ok(output.stdout),
+        false => Err(String::from_utf8_lossy(&output.stderr).into_owned()),
+    }
+}
+
+fn run_text(arguments: &[&str], input: &[u8]) -> Result<String, String> {
+    run(arguments, input).map(|output| String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn decode_and_encode_json() {
+    for entry in read_corpus() {
+        let base64_xdr = entry.base64_xdr.as_bytes();
+        let json = run(&["decode", "--type", &entry.type_name], base64_xdr)
+            .unwrap_or_else(|error| panic!("{}: {}", entry.location, error));
+        let encoded = run_text(&["encode", "--type", &entry.type_name], &json)
+            .unwrap_or_else(|error| panic!("{}: {}", entry.location, error));
+        assert_eq!(encoded.trim_end(), entry.base64_xdr, "{}", entry.location);
+    }
+}
+
+#[test]
+fn decode_and_encode_txrep() {
+    for entry in read_corpus() {
+        let base64_xdr = entry.base64_xdr.as_bytes();
+        let decode = ["decode", "--type", &entry.type_name, "--output", "txrep"];
+        let txrep = run(&decode, base64_xdr)
+            .unwrap_or_else(|error| panic!("{}: {}", entry.location, error));
+        let encode = ["encode", "--type", &entry.type_name, "--input", "txrep"];
+        let encoded = run_text(&encode, &txrep)
+            .unwrap_or_else(|error| panic!("{}: {}", entry.location, error));
+        assert_eq!(encoded.trim_end(), entry.base64_xdr, "{}", entry.location);
+    }
+}
+
+#[test]
+fn convert_transport_encodings() {
+    for entry in read_corpus() {
+        let base64_xdr = entry.base64_xdr.as_bytes();
+        let hex = run(&["convert", "--from", "base64", "--to", "hex"], base64_xdr).unwrap();
+        let raw = run(&["convert", "--from", "hex", "--to", "raw"], &hex).unwrap();
+        let base64 = run_text(&["convert", "--from", "raw", "--to", "base64"], &raw).unwrap();
+        assert_eq!(base64.trim_end(), entry.base64_xdr, "{}", entry.location);
+
+        // the hex encoding can be decoded directly
+        let decode = ["decode", "--type", &entry.type_name, "--input", "hex"];
+        assert_eq!(
+            run(&decode, &hex).unwrap(),
+            run(&["decode", "--type", &entry.type_name], base64_xdr).unwrap(),
+            "{}",
+            entry.location
+        );
+    }
+}
+
+#[test]
+fn guess_types() {
+    for entry in read_corpus() {
+        let guesses = run_text(&["guess"], entry.base64_xdr.as_bytes())
+            .unwrap_or_else(|error| panic!("{}: {}", entry.location, error));
+        assert!(
+            guesses.lines().any(|guess| guess == entry.type_name),
+            "{}: {} is not among the guesses {:?}",
+            entry.location,
+            entry.type_name,
+            guesses
+        );
+    }
+}
+
+#[test]
+fn list_types() {
+    let types = run_text(&["types"], b"").unwrap();
+    let type_names: Vec<&str> = types
+        .lines()
+        .map(|line| line.split(' ').next().unwrap())
+        .collect();
+
+    for entry in read_corpus() {
+        assert!(
+            type_names.contains(&&entry.type_name[..]),
+            "{}: {} is not listed",
+            entry.location,
+            entry.type_name
+        );
+    }
+}
+
+#[test]
+fn invalid_input() {
+    let entry = &read_corpus()[0];
+    let decode = ["decode", "--type", &entry.type_name];
+    assert!(run(&decode, b"not base64!").is_err());
+    assert!(run(
+        &["decode", "--type", "NoSuchType"],
+        entry.base64_xdr.as_bytes()
+    )
+    .is_err());
+    assert!(run(&["convert", "--from", "hex", "--to", "raw"], b"abc").is_err());
+
+    // deeply nested JSON is rejected instead of overflowing the stack
+    let nested = "[".repeat(100_000);
+    let encode = ["encode", "--type", &entry.type_name];
+    assert!(run(&encode, nested.as_bytes())
+        .unwrap_err()
+        .contains("nested too deeply"));
+}
@@ -0,0 +1,320 @@
+//! Derive macro for the trait `XdrCodec` of the crate `substrate-stellar-xdr`
+//!
+//! The derived implementation produces the same wire format as the types generated for
+//! the module `xdr`:
+//!
+//! - structs (with named fields, tuple structs or unit structs) encode their fields in order
+//! - C-like enums encode their discriminant as an `i32`; every variant needs an explicit
+//!   discriminant such as `Payment = 1`
+//! - discriminated unions are enums whose variants carry at most one unnamed field. The type of
+//!   the discriminant is given by `#[xdr(switch = "OperationType")]` on the enum (defaults to `i32`)
+//!   and every variant specifies its discriminant via `#[xdr(case = "OperationType::Payment")]` or
+//!   `#[xdr(case = 1)]`. A variant marked with `#[xdr(default)]` carries the discriminant of all
+//!   cases that are not covered by another variant, just as the `Default` arm of generated unions.
+//!
+//! The generated code refers to the crate as `::substrate_stellar_xdr`. A different path can be
+//! specified via `#[xdr(crate = "path::to::crate")]` on the type.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DataEnum, DeriveInput, Error, Fields, Lit,
+    Meta, NestedMeta, Path, Type, Variant,
+};
+
+/// Derive the XDR decoder/encoder trait `XdrCodec`
+#[proc_macro_derive(XdrCodec, attributes(xdr))]
+pub fn derive_xdr_codec(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_xdr_codec(input) {
+        Ok(expanded) => expanded.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// The attributes of the type that derives `XdrCodec`
+#[derive(Default)]
+struct ContainerAttributes {
+    crate_path: Option<Path>,
+    switch_type: Option<Type>,
+}
+
+/// The attributes of a variant of a discriminated union
+#[derive(Default)]
+struct VariantAttributes {
+    case: Option<TokenStream2>,
+    is_default: bool,
+}
+
+fn parse_xdr_attributes(attributes: &[Attribute]) -> Result<Vec<NestedMeta>, Error> {
+    let mut result = Vec::new();
+    for attribute in attributes
+        .iter()
+        .filter(|attribute| attribute.path.is_ident("xdr"))
+    {
+        match attribute.parse_meta()? {
+            Meta::List(list) => result.extend(list.nested),
+            meta => {
+                return Err(Error::new_spanned(
+                    meta,
+                    "expected an attribute of the form `#[xdr(...)]`",
+                ))
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn parse_string<T: syn::parse::Parse>(lit: &Lit) -> Result<T, Error> {
+    match lit {
+        Lit::Str(string) => string.parse(),
+        _ => Err(Error::new_spanned(lit, "expected a string literal")),
+    }
+}
+
+fn parse_container_attributes(attributes: &[Attribute]) -> Result<ContainerAttributes, Error> {
+    let mut result = ContainerAttributes::default();
+    for meta in parse_xdr_attributes(attributes)? {
+        match meta {
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("crate") => {
+                result.crate_path = Some(parse_string(&name_value.lit)?);
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("switch") => {
+                result.switch_type = Some(parse_string(&name_value.lit)?);
+            }
+            meta => return Err(Error::new_spanned(meta, "unknown xdr attribute")),
+        }
+    }
+    Ok(result)
+}
+
+fn parse_variant_attributes(attributes: &[Attribute]) -> Result<VariantAttributes, Error> {
+    let mut result = VariantAttributes::default();
+    for meta in parse_xdr_attributes(attributes)? {
+        match meta {
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("case") => {
+                result.case = Some(match &name_value.lit {
+                    Lit::Int(int) => quote!(#int),
+                    lit => parse_string(lit)?,
+                });
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("default") => {
+                result.is_default = true;
+            }
+            meta => return Err(Error::new_spanned(meta, "unknown xdr attribute")),
+        }
+    }
+    Ok(result)
+}
+
+fn expand_xdr_codec(mut input: DeriveInput) -> Result<TokenStream2, Error> {
+    let container_attributes = parse_container_attributes(&input.attrs)?;
+    let crate_path = container_attributes
+        .crate_path
+        .clone()
+        .unwrap_or_else(|| parse_quote!(::substrate_stellar_xdr));
+
+    let (encoder, decoder) = match &input.data {
+        Data::Struct(data) => {
+            if container_attributes.switch_type.is_some() {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "only enums can specify a switch type",
+                ));
+            }
+            expand_struct(&crate_path, &data.fields)
+        }
+        Data::Enum(data) => {
+            let is_union = container_attributes.switch_type.is_some()
+                || data.variants.iter().any(|variant| {
+                    !matches!(variant.fields, Fields::Unit) || variant.discriminant.is_none()
+                });
+            if is_union {
+                let switch_type = container_attributes
+                    .switch_type
+                    .clone()
+                    .unwrap_or_else(|| parse_quote!(i32));
+                expand_union(&crate_path, &switch_type, data)?
+            } else {
+                expand_enum(&crate_path, data)
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "XdrCodec cannot be derived for Rust unions",
+            ))
+        }
+    };
+
+    for type_parameter in input.generics.type_params_mut() {
+        type_parameter
+            .bounds
+            .push(parse_quote!(#crate_path::xdr_codec::XdrCodec));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #crate_path::xdr_codec::XdrCodec for #name #type_generics #where_clause {
+            fn to_xdr_buffered(&self, write_stream: &mut #crate_path::streams::WriteStream) {
+                #encoder
+            }
+
+            #[allow(unreachable_patterns)]
+            fn from_xdr_buffered<XdrSource: ::core::convert::AsRef<[u8]>>(
+                read_stream: &mut #crate_path::streams::ReadStream<XdrSource>,
+            ) -> ::core::result::Result<Self, #crate_path::streams::DecodeError> {
+                #decoder
+            }
+        }
+    })
+}
+
+fn expand_struct(crate_path: &Path, fields: &Fields) -> (TokenStream2, TokenStream2) {
+    let codec = quote!(#crate_path::xdr_codec::XdrCodec);
+    match fields {
+        Fields::Named(fields) => {
+            let identifiers: Vec<_> = fields.named.iter().map(|field| &field.ident).collect();
+            let types: Vec<_> = fields.named.iter().map(|field| &field.ty).collect();
+            (
+                quote! { #(#codec::to_xdr_buffered(&self.#identifiers, write_stream);)* },
+                quote! { Ok(Self { #(#identifiers: <#types as #codec>::from_xdr_buffered(read_stream)?,)* }) },
+            )
+        }
+        Fields::Unnamed(fields) => {
+            let indices: Vec<_> = (0..fields.unnamed.len()).map(syn::Index::from).collect();
+            let types: Vec<_> = fields.unnamed.iter().map(|field| &field.ty).collect();
+            (
+                quote! { #(#codec::to_xdr_buffered(&self.#indices, write_stream);)* },
+                quote! { Ok(Self(#(<#types as #codec>::from_xdr_buffered(read_stream)?,)*)) },
+            )
+        }
+        Fields::Unit => (
+            quote! { let _ = write_stream; },
+            quote! { let _ = read_stream; Ok(Self) },
+        ),
+    }
+}
+
+fn expand_enum(crate_path: &Path, data: &DataEnum) -> (TokenStream2, TokenStream2) {
+    let codec = quote!(#crate_path::xdr_codec::XdrCodec);
+    let identifiers: Vec<_> = data.variants.iter().map(|variant| &variant.ident).collect();
+    let values: Vec<_> = data
+        .variants
+        .iter()
+        .map(|variant| &variant.discriminant.as_ref().unwrap().1)
+        .collect();
+
+    let encoder = quote! {
+        let value: i32 = match self {
+            #(Self::#identifiers => #values,)*
+        };
+        #codec::to_xdr_buffered(&value, write_stream);
+    };
+    let decoder = quote! {
+        let enum_value = <i32 as #codec>::from_xdr_buffered(read_stream)?;
+        #(if enum_value == #values { return Ok(Self::#identifiers); })*
+        Err(#crate_path::streams::DecodeError::InvalidEnumDiscriminator {
+            at_position: read_stream.get_position(),
+        })
+    };
+
+    (encoder, decoder)
+}
+
+fn determine_arm_type(variant: &Variant) -> Result<Option<&Type>, Error> {
+    match &variant.fields {
+        Fields::Unit => Ok(None),
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Ok(Some(&fields.unnamed[0].ty)),
+        _ => Err(Error::new_spanned(
+            variant,
+            "the variants of a discriminated union must have at most one unnamed field",
+        )),
+    }
+}
+
+fn expand_union(
+    crate_path: &Path,
+    switch_type: &Type,
+    data: &DataEnum,
+) -> Result<(TokenStream2, TokenStream2), Error> {
+    let codec = quote!(#crate_path::xdr_codec::XdrCodec);
+    let mut encoders = Vec::new();
+    let mut decoders = Vec::new();
+    let mut default_decoder = None;
+
+    for variant in data.variants.iter() {
+        let attributes = parse_variant_attributes(&variant.attrs)?;
+        let identifier = &variant.ident;
+        let arm_type = determine_arm_type(variant)?;
+
+        if attributes.is_default {
+            if attributes.case.is_some() || arm_type.is_none() || default_decoder.is_some() {
+                return Err(Error::new_spanned(
+                    variant,
+                    "there must be at most one default variant and it must only carry the discriminant",
+                ));
+            }
+            encoders.push(
+                quote! { Self::#identifier(code) => #codec::to_xdr_buffered(code, write_stream), },
+            );
+            default_decoder = Some(quote! { code => Ok(Self::#identifier(code)), });
+            continue;
+        }
+
+        let case = attributes.case.ok_or_else(|| {
+            Error::new_spanned(
+                variant,
+                "every variant of a discriminated union needs an `#[xdr(case = ...)]` attribute",
+            )
+        })?;
+        let write_discriminant = quote! {
+            let discriminant: #switch_type = #case;
+            #codec::to_xdr_buffered(&discriminant, write_stream);
+        };
+
+        match arm_type {
+            Some(arm_type) => {
+                encoders.push(quote! {
+                    Self::#identifier(value) => {
+                        #write_discriminant
+                        #codec::to_xdr_buffered(value, write_stream);
+                    }
+                });
+                decoders.push(quote! {
+                    #case => Ok(Self::#identifier(<#arm_type as #codec>::from_xdr_buffered(read_stream)?)),
+                });
+            }
+            None => {
+                encoders.push(quote! { Self::#identifier => { #write_discriminant } });
+                decoders.push(quote! { #case => Ok(Self::#identifier), });
+            }
+        }
+    }
+
+    let default_decoder = default_decoder.unwrap_or_else(|| {
+        quote! {
+            _ => Err(#crate_path::streams::DecodeError::InvalidEnumDiscriminator {
+                at_position: read_stream.get_position(),
+            }),
+        }
+    });
+
+    let encoder = quote! {
+        match self {
+            #(#encoders)*
+        }
+    };
+    let decoder = quote! {
+        match <#switch_type as #codec>::from_xdr_buffered(read_stream)? {
+            #(#decoders)*
+            #default_decoder
+        }
+    };
+
+    Ok((encoder, decoder))
+}
@@ -0,0 +1,16 @@
+import { createHash } from "crypto";
+import { readdirSync, readFileSync } from "fs";
+import { join } from "path";
+
+// Determine the SHA-256 checksums of the `.x` files in the given directory, ordered by file name
+//
+// The checksums identify the input of the generation in the header of the generated code.
+export function determineXdrChecksums(directory: string): Array<[string, string]> {
+  return readdirSync(directory)
+    .filter((fileName) => fileName.endsWith(".x"))
+    .sort()
+    .map((fileName): [string, string] => [
+      fileName,
+      createHash("sha256").update(readFileSync(join(directory, fileName))).digest("hex"),
+    ]);
+}
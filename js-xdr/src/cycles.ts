@@ -0,0 +1,99 @@
+import { ReferableXdrType } from "../types/types";
+
+// XDR definitions can refer to each other in cycles, e.g., the union `ClaimPredicate` contains
+// an optional `ClaimPredicate`. A Rust type that contains itself inline has an infinite size, so
+// such cycles need to be broken by a `Box`. Only references that are stored inline need a `Box`:
+// variable length arrays are already stored on the heap.
+//
+// The cycles are determined by computing the strongly connected components of the graph of inline
+// references between all definitions. Typedefs are nodes of this graph as well, so that cycles
+// through typedefs are found. A reference is wrapped into a `Box` exactly if it points into the
+// strongly connected component of the definition that contains the reference.
+
+// Determine the names of the definitions that a type contains inline
+function determineInlineReferences(type: ReferableXdrType): string[] {
+  switch (type.type) {
+    case "reference":
+      return [type.name];
+    case "option":
+    case "array":
+      return determineInlineReferences(type.innerType);
+    default:
+      return [];
+  }
+}
+
+// Compute the strongly connected components of the graph of inline references using Tarjan's algorithm
+//
+// `memberTypes` contains for every definition the types of its members, i.e., the fields of a struct,
+// the arms of a union or the aliased type of a typedef
+function determineComponents(memberTypes: Record<string, ReferableXdrType[]>): Record<string, number> {
+  const components: Record<string, number> = {};
+  const indices: Record<string, number> = {};
+  const lowLinks: Record<string, number> = {};
+  const stack: string[] = [];
+  const onStack: Record<string, boolean> = {};
+  let nextIndex = 0;
+  let nextComponent = 0;
+
+  const visit = (name: string) => {
+    indices[name] = nextIndex;
+    lowLinks[name] = nextIndex;
+    nextIndex++;
+    stack.push(name);
+    onStack[name] = true;
+
+    (memberTypes[name] || []).forEach((memberType) => {
+      determineInlineReferences(memberType).forEach((reference) => {
+        if (indices[reference] === undefined) {
+          visit(reference);
+          lowLinks[name] = Math.min(lowLinks[name], lowLinks[reference]);
+        } else if (onStack[reference]) {
+          lowLinks[name] = Math.min(lowLinks[name], indices[reference]);
+        }
+      });
+    });
+
+    if (lowLinks[name] === indices[name]) {
+      while (true) {
+        const member = stack.pop();
+        if (member === undefined) break;
+        onStack[member] = false;
+        components[member] = nextComponent;
+        if (member === name) break;
+      }
+      nextComponent++;
+    }
+  };
+
+  Object.keys(memberTypes).forEach((name) => {
+    if (indices[name] === undefined) visit(name);
+  });
+
+  return components;
+}
+
+export type CycleBreaker = (owner: string, type: ReferableXdrType) => ReferableXdrType;
+
+// Returns a function that wraps all references of a member type into a `Box` that are part of a cycle
+// through the definition `owner`
+export function determineCycleBreaker(memberTypes: Record<string, ReferableXdrType[]>): CycleBreaker {
+  const components = determineComponents(memberTypes);
+
+  const breakCycles: CycleBreaker = (owner, type) => {
+    switch (type.type) {
+      case "reference":
+        return components[type.name] !== undefined && components[type.name] === components[owner]
+          ? { type: "box", innerType: type }
+          : type;
+      case "option":
+        return { type: "option", innerType: breakCycles(owner, type.innerType) };
+      case "array":
+        return { ...type, innerType: breakCycles(owner, type.innerType) };
+      default:
+        return type;
+    }
+  };
+
+  return breakCycles;
+}
@@ -0,0 +1,179 @@
+// xdrgen precedes every definition in the generated JavaScript file by a comment that contains
+// the original XDR source of the definition:
+//
+// // === xdr source ============================================================
+// //
+// //   struct Price
+// //   {
+// //       int32 n; // numerator
+// //       int32 d; // denominator
+// //   };
+// //
+// // ===========================================================================
+// xdr.struct("Price", [
+//
+// This module extracts these XDR sources together with the comments on the members of the definitions
+
+export interface TypeDocumentation {
+  source: string[];
+  members: Record<string, string[]>;
+  identifiers: Record<string, string>;
+}
+
+const SOURCE_START = "// === xdr source";
+const SOURCE_END = "// ====";
+const DEFINITION_REGEX = /^xdr\.(?:struct|enum|union|typedef|const)\("([^"]+)"/;
+
+// XDR identifiers are written in different cases in the XDR source and in the generated JavaScript
+// file, e.g., `MEMO_NONE` and `memoNone`
+export function normalizeName(name: string): string {
+  return name.replace(/_/g, "").toLowerCase();
+}
+
+function splitCodeAndComment(line: string, state: { inBlockComment: boolean }): { code: string; comment: string } {
+  let code = "";
+  let comment = "";
+  let index = 0;
+
+  while (index < line.length) {
+    if (state.inBlockComment) {
+      const end = line.indexOf("*/", index);
+      comment += " " + line.slice(index, end === -1 ? line.length : end);
+      if (end === -1) break;
+      state.inBlockComment = false;
+      index = end + 2;
+    } else if (line.startsWith("//", index)) {
+      comment += " " + line.slice(index + 2);
+      break;
+    } else if (line.startsWith("/*", index)) {
+      state.inBlockComment = true;
+      index += 2;
+    } else {
+      code += line[index];
+      index++;
+    }
+  }
+
+  return { code, comment: comment.replace(/^\s*\*+/, "").trim() };
+}
+
+function countCharacters(text: string, character: string): number {
+  return text.split(character).length - 1;
+}
+
+function parseMembers(source: string[]): Pick<TypeDocumentation, "members" | "identifiers"> {
+  const members: Record<string, string[]> = {};
+  const identifiers: Record<string, string> = {};
+  const state = { inBlockComment: false };
+  let depth = 0;
+  let pendingComments: string[] = [];
+
+  source.forEach((line) => {
+    const { code, comment } = splitCodeAndComment(line, state);
+    const depthBefore = depth;
+    depth += countCharacters(code, "{") - countCharacters(code, "}");
+
+    if (code.trim() === "") {
+      if (comment !== "") pendingComments.push(comment);
+      return;
+    }
+
+    const names: string[] = [];
+    const enumCaseRegex = /(\w+)\s*=\s*-?\w+/g;
+    const unionCaseRegex = /case\s+(\w+)\s*:/g;
+    let match: RegExpExecArray | null;
+    while ((match = enumCaseRegex.exec(code)) !== null) names.push(match[1]);
+    while ((match = unionCaseRegex.exec(code)) !== null) names.push(match[1]);
+
+    const declaration = /(\w+)\s*(?:\[[^\]]*\]|<[^>]*>)?\s*;\s*$/.exec(code);
+    if (declaration !== null && depthBefore >= 1 && depth === 1 && declaration[1] !== "void") {
+      names.push(declaration[1]);
+    }
+
+    const comments = comment !== "" ? [...pendingComments, comment] : pendingComments;
+    names.forEach((name) => {
+      identifiers[normalizeName(name)] = name;
+      if (comments.length > 0) members[normalizeName(name)] = comments;
+    });
+    pendingComments = [];
+  });
+
+  return { members, identifiers };
+}
+
+// Extract the XDR source and the member comments for every definition in the generated JavaScript file
+export function parseDocumentation(generatedSource: string): Record<string, TypeDocumentation> {
+  const documentation: Record<string, TypeDocumentation> = {};
+  const lines = generatedSource.split(/\r?\n/);
+
+  let source: string[] | undefined;
+  let inSource = false;
+
+  lines.forEach((line) => {
+    const trimmedLine = line.trim();
+
+    if (inSource && source !== undefined) {
+      if (trimmedLine.startsWith(SOURCE_END)) {
+        inSource = false;
+      } else {
+        source.push(trimmedLine.replace(/^\/\/ {0,3}/, "").replace(/\s+$/, ""));
+      }
+    } else if (trimmedLine.startsWith(SOURCE_START)) {
+      inSource = true;
+      source = [];
+    } else if (source !== undefined && trimmedLine !== "") {
+      const definition = DEFINITION_REGEX.exec(trimmedLine);
+      if (definition !== null) {
+        while (source.length > 0 && source[0] === "") source.shift();
+        while (source.length > 0 && source[source.length - 1] === "") source.pop();
+        documentation[definition[1]] = { source, ...parseMembers(source) };
+      }
+      source = undefined;
+    }
+  });
+
+  return documentation;
+}
+
+// Escape characters that rustdoc would interpret as links or HTML tags
+function escapeMarkdown(text: string): string {
+  return text.replace(/([\[\]<>])/g, "\\$1");
+}
+
+// Determine the identifier of a member (e.g., `MEMO_NONE`) as written in the XDR source
+export function determineXdrIdentifier(documentation: TypeDocumentation | undefined, name: string): string | undefined {
+  return documentation === undefined ? undefined : documentation.identifiers[normalizeName(name)];
+}
+
+// Render comment lines as rustdoc
+export function renderComments(comments: string[], indentation: string): string {
+  return comments.map((comment) => `${indentation}/// ${escapeMarkdown(comment)}\n`).join("");
+}
+
+// Render the comments on the members of a definition as rustdoc
+//
+// A member can be identified by several names, e.g., a union arm by its case and its arm name
+export function renderMemberComments(
+  documentation: TypeDocumentation | undefined,
+  names: string[],
+  indentation: string
+): string {
+  if (documentation === undefined) return "";
+
+  const comments: string[] = [];
+  names.forEach((name) => {
+    (documentation.members[normalizeName(name)] || []).forEach((comment) => {
+      if (comments.indexOf(comment) === -1) comments.push(comment);
+    });
+  });
+
+  return renderComments(comments, indentation);
+}
+
+// Render the XDR source of a definition as rustdoc
+export function renderSource(documentation: TypeDocumentation | undefined): string {
+  if (documentation === undefined) return "";
+
+  const sourceLines = documentation.source.map((line) => (line === "" ? "///\n" : `/// ${line}\n`));
+  return "///\n/// ```text\n" + sourceLines.join("") + "/// ```\n";
+}
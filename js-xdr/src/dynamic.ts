@@ -0,0 +1,102 @@
+import { lengthToString, ReferableXdrType, XdrType } from "../types/types";
+
+// Generate a Rust expression of type `SchemaType` for the given type
+function generateSchemaType(type: ReferableXdrType): string {
+  switch (type.type) {
+    case "uhyper":
+      return "SchemaType::UnsignedHyper";
+    case "hyper":
+      return "SchemaType::Hyper";
+    case "uint":
+      return "SchemaType::UnsignedInt";
+    case "int":
+      return "SchemaType::Int";
+    case "bool":
+      return "SchemaType::Bool";
+    case "void":
+      throw new Error("The type void cannot be described in the schema");
+    case "opaque":
+      return `SchemaType::Opaque(${lengthToString(type.length)})`;
+    case "limitedVarOpaque":
+      return `SchemaType::VarOpaque(${lengthToString(type.maxLength)})`;
+    case "unlimitedVarOpaque":
+      return "SchemaType::VarOpaque(i32::MAX)";
+    case "limitedString":
+      return `SchemaType::String(${lengthToString(type.maxLength)})`;
+    case "unlimitedString":
+      return "SchemaType::String(i32::MAX)";
+    case "array":
+      return `SchemaType::Array(&${generateSchemaType(type.innerType)}, ${lengthToString(type.length)})`;
+    case "limitedVarArray":
+      return `SchemaType::VarArray(&${generateSchemaType(type.innerType)}, ${lengthToString(type.maxLength)})`;
+    case "unlimitedVarArray":
+      return `SchemaType::VarArray(&${generateSchemaType(type.innerType)}, i32::MAX)`;
+    case "option":
+      return `SchemaType::Optional(&${generateSchemaType(type.innerType)})`;
+    case "box":
+      // boxes only break cycles of the Rust types, they do not change the encoding
+      return generateSchemaType(type.innerType);
+    case "reference":
+      return `SchemaType::Reference("${type.name}")`;
+  }
+}
+
+// Generate a Rust expression of type `SchemaDefinition` for the given type
+function generateSchemaDefinition(type: XdrType): string {
+  switch (type.type) {
+    case "enum": {
+      const cases = type.cases.map(
+        (enumCase) => `EnumCaseMetadata { name: "${enumCase.xdrName}", value: ${enumCase.value} }`
+      );
+      return `SchemaDefinition::Enum { cases: &[${cases.join(", ")}], open: ${type.open} }`;
+    }
+    case "struct": {
+      const fields = type.fields.map((field) => `("${field.name}", ${generateSchemaType(field.fieldType)})`);
+      return `SchemaDefinition::Struct(&[${fields.join(", ")}])`;
+    }
+    case "union": {
+      const arms = type.arms.map(
+        (arm) =>
+          `SchemaUnionArm { case_name: "${arm.caseName}", discriminant: ${arm.discriminant}, arm: ${
+            arm.armName === undefined || arm.armType === undefined
+              ? "None"
+              : `Some(("${arm.armName}", ${generateSchemaType(arm.armType)}))`
+          } }`
+      );
+      return `SchemaDefinition::Union { switch_name: "${type.switchName}", switch: ${generateSchemaType(
+        type.switchOn
+      )}, arms: &[${arms.join(", ")}], has_default_arm: ${type.hasDefaultArm} }`;
+    }
+    case "newtype":
+      return `SchemaDefinition::Typedef(${generateSchemaType(type.innerType)})`;
+    default:
+      return `SchemaDefinition::Typedef(${generateSchemaType(type)})`;
+  }
+}
+
+// Generate the list of all type names and the function to look up the schema of all types by name
+//
+// The schema only describes the structure of the types, so it covers all types regardless of the
+// cargo features of the generated Rust types.
+export function generateSchemaLookup(types: Record<string, XdrType>): string {
+  const lookupArms = Object.keys(types).map(
+    (typeName) => `        "${typeName}" => Some(&${generateSchemaDefinition(types[typeName])}),\n`
+  );
+
+  const typeNames = Object.keys(types).map((typeName) => `    "${typeName}",\n`);
+
+  return `/// The names of all XDR types in the schema
+#[cfg(feature = "dynamic")]
+pub const SCHEMA_TYPE_NAMES: &[&str] = &[
+${typeNames.join("")}];
+
+/// Look up the schema of an XDR type by its name
+#[cfg(feature = "dynamic")]
+pub fn schema_definition(name: &str) -> Option<&'static SchemaDefinition> {
+    match name {
+${lookupArms.join("")}        _ => None,
+    }
+}
+
+`;
+}
@@ -1,22 +1,65 @@
-import { pascalCase } from "change-case";
-import { EnumType } from "../types/types";
+import { constantCase, pascalCase } from "change-case";
+import { EnumCase, EnumType } from "../types/types";
+import { determineXdrIdentifier, renderMemberComments, TypeDocumentation } from "./documentation";
 
 export type EnumDefinition = Record<string, number>;
 
-export function processEnum(name: string, enumDefinition: EnumDefinition): EnumType {
+// An open enum decodes values that are not defined as the case `Unknown(value)` instead of failing,
+// so that decoding does not break when a protocol upgrade adds new cases
+export function processEnum(
+  name: string,
+  enumDefinition: EnumDefinition,
+  open: boolean,
+  documentation?: TypeDocumentation
+): EnumType {
   const subTypes: string[] = [];
   const subReaders: string[] = [];
+  const valueWriters: string[] = [];
+  const nameWriters: string[] = [];
+  const nameReaders: string[] = [];
+  const cases: EnumCase[] = [];
 
   Object.entries(enumDefinition).forEach(([key, constant]) => {
-    const stringifiedKey = `    ${pascalCase(key)} = ${constant}`;
+    const xdrName = determineXdrIdentifier(documentation, key) || constantCase(key);
+    cases.push({ name: key, xdrName, identifier: pascalCase(key), value: constant });
+    // explicit discriminants are not allowed in enums with fields such as `Unknown(i32)`
+    const stringifiedKey = `${renderMemberComments(documentation, [key], "    ")}    ${pascalCase(key)}${
+      open ? "" : ` = ${constant}`
+    }`;
     subTypes.push(stringifiedKey);
     subReaders.push(`            ${constant} => Ok(${name}::${pascalCase(key)}),`);
+    valueWriters.push(`            ${name}::${pascalCase(key)} => ${constant},`);
+    nameWriters.push(`            ${name}::${pascalCase(key)} => "${xdrName}",`);
+    nameReaders.push(`            "${xdrName}" => Ok(${name}::${pascalCase(key)}),`);
   });
 
+  if (open) {
+    subTypes.push(`    /// A value that is not defined, e.g., a case of a later protocol version
+    ///
+    /// Never construct this case with a defined value: it would not be equal to the defined case
+    /// although it has the same encoding. Use \`${name}::try_from\` to convert an integer instead.
+    Unknown(i32)`);
+    valueWriters.push(`            ${name}::Unknown(value) => value,`);
+    nameWriters.push(`            ${name}::Unknown(_) => "UNKNOWN",`);
+  }
+
+  // unknown values of open enums are read from and displayed as their integer value
+  const unknownValueReader = open ? `Ok(${name}::Unknown(value))` : "Err(InvalidEnumValue { value })";
+  const valueWriter = open ? `match value {\n${valueWriters.join("\n")}\n        }` : "value as i32";
+  const unknownNameReader = open
+    ? `name.parse::<i32>().ok().and_then(|value| ${name}::try_from(value).ok()).ok_or(UnknownEnumName)`
+    : "Err(UnknownEnumName)";
+  const displayWriter = open
+    ? `match self {
+            ${name}::Unknown(value) => write!(formatter, "{}", value),
+            _ => formatter.write_str(self.name()),
+        }`
+    : "formatter.write_str(self.name())";
+
   const typeDefinition = `pub enum ${name} {\n${subTypes.join(",\n")}\n}`;
   const typeImplementation = `
     fn to_xdr_buffered(&self, write_stream: &mut WriteStream) {
-        let value = *self as i32;
+        let value = i32::from(*self);
         value.to_xdr_buffered(write_stream);
     }
 
@@ -24,11 +67,67 @@ export function processEnum(name: string, enumDefinition: EnumDefinition): EnumT
         read_stream: &mut ReadStream<T>,
     ) -> Result<Self, DecodeError> {
         let enum_value = i32::from_xdr_buffered(read_stream)?;
-        match enum_value {
+        ${name}::try_from(enum_value)
+            .map_err(|_| DecodeError::InvalidEnumDiscriminator {at_position: read_stream.get_position()})
+    }`;
+
+  const allCases = cases.map((enumCase) => `${name}::${enumCase.identifier}`).join(", ");
+  const additionalImplementations = [
+    `impl ${name} {
+    /// All cases of this enum in the order of the XDR definition
+    pub const ALL: [${name}; ${cases.length}] = [${allCases}];
+
+    /// Returns all cases of this enum in the order of the XDR definition
+    pub fn variants() -> &'static [${name}] {
+        &${name}::ALL
+    }
+
+    /// Returns the name of this case as in the XDR definition${open ? ", `UNKNOWN` for unknown values" : ""}
+    pub fn name(&self) -> &'static str {
+        match self {
+${nameWriters.join("\n")}
+        }
+    }
+}`,
+    `impl TryFrom<i32> for ${name} {
+    type Error = InvalidEnumValue;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
 ${subReaders.join("\n")}
-            _ => Err(DecodeError::InvalidEnumDiscriminator {at_position: read_stream.get_position()})
+            _ => ${unknownValueReader},
         }
-    }`;
+    }
+}`,
+    `impl From<${name}> for i32 {
+    fn from(value: ${name}) -> Self {
+        ${valueWriter}
+    }
+}`,
+    `impl FromStr for ${name} {
+    type Err = UnknownEnumName;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+${nameReaders.join("\n")}
+            _ => ${unknownNameReader},
+        }
+    }
+}`,
+    `impl fmt::Display for ${name} {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        ${displayWriter}
+    }
+}`,
+  ];
 
-  return { type: "enum", typeDefinition, typeImplementation, noOfCases: Object.entries(enumDefinition).length };
+  return {
+    type: "enum",
+    typeDefinition,
+    typeImplementation,
+    additionalImplementations,
+    noOfCases: cases.length,
+    cases,
+    open,
+  };
 }
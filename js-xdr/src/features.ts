@@ -0,0 +1,151 @@
+import { readdirSync, readFileSync } from "fs";
+import { basename, join } from "path";
+
+import { determineDependencies, XdrType } from "../types/types";
+import { normalizeName } from "./documentation";
+
+// Every `.x` file of the Stellar XDR specification becomes a cargo feature of the generated crate,
+// e.g., `Stellar-ledger-entries.x` becomes the feature `ledger-entries`. A feature enables all types
+// defined in its file and the features of all files these types depend on.
+
+export interface FeatureAssignment {
+  // the feature of every type, types are identified by their name
+  typeFeatures: Record<string, string>;
+  // the features that every feature depends on directly
+  featureDependencies: Record<string, string[]>;
+}
+
+function determineFeatureName(fileName: string): string {
+  return basename(fileName, ".x")
+    .replace(/^Stellar-/, "")
+    .toLowerCase();
+}
+
+function stripComments(source: string): string {
+  return source
+    .replace(/\/\*[\s\S]*?\*\//g, "")
+    .replace(/\/\/.*$/gm, "")
+    .replace(/^%.*$/gm, "");
+}
+
+// Determine the names of the top level definitions in the source of an `.x` file
+//
+// Nested definitions (e.g., the anonymous union `ext` in a struct) are not found here. xdrgen
+// names them after their parent type.
+function parseDefinitionNames(source: string): string[] {
+  const code = stripComments(source);
+  const names: string[] = [];
+  let match: RegExpExecArray | null;
+
+  const complexRegex = /\b(?:struct|enum|union)\s+([A-Za-z_]\w*)/g;
+  while ((match = complexRegex.exec(code)) !== null) {
+    if (match[1] !== "switch") names.push(match[1]);
+  }
+
+  const typedefRegex = /\btypedef\b[^;]*?([A-Za-z_]\w*)\s*(?:\[[^\]]*\]|<[^>]*>)?\s*;/g;
+  while ((match = typedefRegex.exec(code)) !== null) {
+    names.push(match[1]);
+  }
+
+  return names;
+}
+
+// Read the `.x` files in the given directory and determine the feature of every definition
+export function parseXdrDirectory(directory: string): Record<string, string> {
+  const definitionFeatures: Record<string, string> = {};
+
+  readdirSync(directory)
+    .filter((fileName) => fileName.endsWith(".x"))
+    .sort()
+    .forEach((fileName) => {
+      const feature = determineFeatureName(fileName);
+      parseDefinitionNames(readFileSync(join(directory, fileName), "utf8")).forEach((name) => {
+        definitionFeatures[normalizeName(name)] = feature;
+      });
+    });
+
+  return definitionFeatures;
+}
+
+export function assertAcyclicFeatures(featureDependencies: Record<string, string[]>) {
+  const visiting: Record<string, boolean> = {};
+  const visited: Record<string, boolean> = {};
+
+  const visit = (feature: string, path: string[]) => {
+    if (visited[feature]) return;
+    if (visiting[feature]) {
+      throw new Error(`The features depend on each other in a cycle: ${[...path, feature].join(" -> ")}`);
+    }
+
+    visiting[feature] = true;
+    featureDependencies[feature].forEach((dependency) => visit(dependency, [...path, feature]));
+    visiting[feature] = false;
+    visited[feature] = true;
+  };
+
+  Object.keys(featureDependencies).forEach((feature) => visit(feature, []));
+}
+
+// Assign a feature to every type and determine the dependencies between the features
+//
+// `definitionFeatures` maps the normalized names of the top level definitions to their features.
+// Nested definitions get the feature of the type that refers to them.
+export function determineFeatureAssignment(
+  types: Record<string, XdrType>,
+  definitionFeatures: Record<string, string>
+): FeatureAssignment {
+  const typeFeatures: Record<string, string> = {};
+  Object.keys(types).forEach((typeName) => {
+    const feature = definitionFeatures[normalizeName(typeName)];
+    if (feature !== undefined) typeFeatures[typeName] = feature;
+  });
+
+  let changed = true;
+  while (changed) {
+    changed = false;
+    Object.keys(typeFeatures).forEach((typeName) => {
+      Object.keys(determineDependencies(types[typeName])).forEach((dependency) => {
+        if (typeFeatures[dependency] === undefined && types[dependency] !== undefined) {
+          typeFeatures[dependency] = typeFeatures[typeName];
+          changed = true;
+        }
+      });
+    });
+  }
+
+  const unassignedTypes = Object.keys(types).filter((typeName) => typeFeatures[typeName] === undefined);
+  if (unassignedTypes.length > 0) {
+    throw new Error(`Cannot determine the .x file of the types ${unassignedTypes.join(", ")}`);
+  }
+
+  const featureDependencies: Record<string, string[]> = {};
+  Object.values(definitionFeatures).forEach((feature) => {
+    featureDependencies[feature] = [];
+  });
+  Object.keys(types).forEach((typeName) => {
+    const feature = typeFeatures[typeName];
+    Object.keys(determineDependencies(types[typeName])).forEach((dependency) => {
+      const dependencyFeature = typeFeatures[dependency];
+      if (dependencyFeature === undefined || dependencyFeature === feature) return;
+      if (featureDependencies[feature].indexOf(dependencyFeature) === -1) {
+        featureDependencies[feature].push(dependencyFeature);
+      }
+    });
+  });
+  Object.values(featureDependencies).forEach((dependencies) => dependencies.sort());
+
+  assertAcyclicFeatures(featureDependencies);
+
+  return { typeFeatures, featureDependencies };
+}
+
+// Render the features as the `[features]` entries of the `Cargo.toml` of the generated crate
+export function renderCargoFeatures(featureDependencies: Record<string, string[]>): string {
+  const features = Object.keys(featureDependencies).sort();
+  const renderList = (list: string[]) => `[${list.map((feature) => `"${feature}"`).join(", ")}]`;
+
+  return (
+    `all-types = ${renderList(features)}\n` +
+    features.map((feature) => `${feature} = ${renderList(featureDependencies[feature])}\n`).join("")
+  );
+}
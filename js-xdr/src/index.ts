@@ -1,39 +1,76 @@
-import { DefinitionFactory, XdrType } from "../types/types";
+import { readFileSync } from "fs";
+
+import { DefinitionFactory, ReferableXdrType, XdrType } from "../types/types";
+import { determineCycleBreaker } from "./cycles";
+import { determineXdrChecksums } from "./checksums";
+import { parseDocumentation, TypeDocumentation } from "./documentation";
+import { determineFeatureAssignment, parseXdrDirectory } from "./features";
 import { processEnum } from "./enum";
-import { processStruct } from "./struct";
+import { writeSchema } from "./schema";
+import { processStruct, StructDefinition } from "./struct";
+import { processTypedef } from "./typedef";
 import { processUnion, UnionDefinition } from "./union";
-import { initializeOutputPath, generateXdrDefinition, copyStaticFiles } from "./writer";
+import {
+  initializeOutputPath,
+  generateXdrDefinition,
+  copyStaticFiles,
+  writeCargoFeatures,
+  determineTypeFeatures,
+} from "./writer";
 
 if (process.env.DESTINATION === undefined) {
   throw new Error(`The environment variable "DESTINATION" is not defined`);
 }
 
 const outputPath: string = process.env.DESTINATION;
+// emit typedefs as newtypes instead of type aliases
+const newtypeTypedefs = process.env.NEWTYPE_TYPEDEFS === "true";
+// comma separated names of enums that decode undefined values as `Unknown(value)` instead of failing
+const openEnums = (process.env.OPEN_ENUMS || "")
+  .split(",")
+  .map((name) => name.trim())
+  .filter((name) => name !== "");
+// the options that change the generated code are recorded in its header, see `check-generated`
+const generatorOptions = [
+  ...(newtypeTypedefs ? ["NEWTYPE_TYPEDEFS=true"] : []),
+  ...(openEnums.length > 0 ? [`OPEN_ENUMS=${openEnums.join(",")}`] : []),
+];
 initializeOutputPath(outputPath);
 
+// the JavaScript file generated by xdrgen contains the original XDR source of every definition
+const documentation: Record<string, TypeDocumentation> =
+  process.env.SOURCE_FILE === undefined ? {} : parseDocumentation(readFileSync(process.env.SOURCE_FILE, "utf8"));
+
 export function config(definitionFactory: DefinitionFactory) {
   const constants: Record<string, number> = {};
   const types: Record<string, XdrType> = {};
 
+  const structs: Array<{ name: string; structDefinition: StructDefinition }> = [];
   const unions: Array<{ name: string; unionDefinition: UnionDefinition }> = [];
+  const memberTypes: Record<string, ReferableXdrType[]> = {};
 
   definitionFactory({
     typedef: (name, type) => {
-      types[name] = type;
+      types[name] = newtypeTypedefs ? processTypedef(name, type as ReferableXdrType) : type;
+      memberTypes[name] = [type as ReferableXdrType];
     },
 
     enum: (name, enumDefinition) => {
-      types[name] = processEnum(name, enumDefinition);
+      types[name] = processEnum(name, enumDefinition, openEnums.indexOf(name) !== -1, documentation[name]);
     },
 
     struct: (name, structDefinition) => {
-      types[name] = processStruct(name, structDefinition);
+      // postpone construction of struct type because we need to know all
+      // definitions in order to detect cycles
+      structs.push({ name, structDefinition });
+      memberTypes[name] = structDefinition.map(([, type]) => type);
     },
 
     union: (name, unionDefinition) => {
       // postpone construction of union type because we need to make sure
       // that all enums are already defined
       unions.push({ name, unionDefinition });
+      memberTypes[name] = Object.values(unionDefinition.arms);
     },
 
     const: (name, value) => {
@@ -69,13 +106,54 @@ export function config(definitionFactory: DefinitionFactory) {
     uhyper: () => ({ type: "uhyper" }),
   });
 
+  openEnums.forEach((name) => {
+    if (types[name] === undefined || types[name].type !== "enum") {
+      throw new Error(`The type "${name}" in OPEN_ENUMS is not an enum`);
+    }
+  });
+
+  const breakCycles = determineCycleBreaker(memberTypes);
+
+  structs.forEach(({ name, structDefinition }) => {
+    const acyclicStructDefinition: StructDefinition = structDefinition.map(([key, type]) => [
+      key,
+      breakCycles(name, type),
+    ]);
+
+    types[name] = processStruct(name, acyclicStructDefinition, documentation[name]);
+  });
+
   unions.forEach(({ name, unionDefinition }) => {
     const resolvedSwitchType =
       unionDefinition.switchOn.type === "reference" ? types[unionDefinition.switchOn.name] : unionDefinition.switchOn;
 
-    types[name] = processUnion(name, unionDefinition, resolvedSwitchType);
+    const arms: Record<string, ReferableXdrType> = {};
+    Object.entries(unionDefinition.arms).forEach(([armName, type]) => {
+      arms[armName] = breakCycles(name, type);
+    });
+
+    types[name] = processUnion(name, { ...unionDefinition, arms }, resolvedSwitchType, documentation[name]);
   });
 
+  // the .x files determine the cargo features of the generated crate, otherwise all types
+  // that are not required by the root types are behind the feature "all-types"
+  const featureAssignment =
+    process.env.XDR_DIRECTORY === undefined
+      ? undefined
+      : determineFeatureAssignment(types, parseXdrDirectory(process.env.XDR_DIRECTORY));
+
+  const typeFeatures = determineTypeFeatures(types, featureAssignment);
+  const featureDependencies = featureAssignment && featureAssignment.featureDependencies;
+
   copyStaticFiles(outputPath);
-  generateXdrDefinition(types, constants, outputPath);
+  // when generating one module per protocol version, the final step merges the features of all versions
+  if (featureDependencies !== undefined && process.env.VERSION_MODULE === undefined) {
+    writeCargoFeatures(outputPath, featureDependencies);
+  }
+  const xdrChecksums = process.env.XDR_DIRECTORY === undefined ? [] : determineXdrChecksums(process.env.XDR_DIRECTORY);
+  generateXdrDefinition(types, constants, documentation, typeFeatures, xdrChecksums, generatorOptions, outputPath);
+
+  if (process.env.SCHEMA_FILE !== undefined) {
+    writeSchema(process.env.SCHEMA_FILE, { constants, types, typeFeatures, featureDependencies });
+  }
 }
@@ -0,0 +1,84 @@
+import { XdrType } from "../types/types";
+
+function determineKind(type: XdrType): string {
+  switch (type.type) {
+    case "struct":
+      return "XdrTypeKind::Struct";
+    case "enum":
+      return "XdrTypeKind::Enum";
+    case "union":
+      return "XdrTypeKind::Union";
+    default:
+      return "XdrTypeKind::Typedef";
+  }
+}
+
+// Generate a Rust expression of type `XdrTypeMetadata` that describes the given type
+function generateTypeMetadata(typeName: string, type: XdrType): string {
+  const fields = type.type === "struct" ? type.fields.map((field) => `"${field.name}"`) : [];
+  const unionArms =
+    type.type === "union"
+      ? type.arms.map(
+          (arm) =>
+            `UnionArmMetadata { case_name: "${arm.caseName}", discriminant: ${arm.discriminant}, arm_name: ${
+              arm.armName === undefined ? "None" : `Some("${arm.armName}")`
+            } }`
+        )
+      : [];
+  const enumCases =
+    type.type === "enum"
+      ? type.cases.map((enumCase) => `EnumCaseMetadata { name: "${enumCase.xdrName}", value: ${enumCase.value} }`)
+      : [];
+
+  return `XdrTypeMetadata {
+    name: "${typeName}",
+    kind: ${determineKind(type)},
+    fields: &[${fields.join(", ")}],
+    union_arms: &[${unionArms.join(", ")}],
+    enum_cases: &[${enumCases.join(", ")}],
+}`;
+}
+
+// Generate the implementation of the trait `XdrType` for a struct, enum, union or newtype
+export function generateXdrTypeImplementation(typeName: string, type: XdrType): string {
+  return `impl XdrType for ${typeName} {
+    fn metadata() -> &'static XdrTypeMetadata {
+        &${generateTypeMetadata(typeName, type)}
+    }
+}`;
+}
+
+// Generate the functions to look up the metadata of all types by name
+//
+// Typedefs that are emitted as type aliases do not implement the trait `XdrType`, so their metadata
+// is defined inline
+export function generateMetadataLookup(types: Record<string, XdrType>, typePrefixes: Record<string, string>): string {
+  const isComplex = (type: XdrType) =>
+    type.type === "enum" || type.type === "struct" || type.type === "union" || type.type === "newtype";
+  const metadataExpression = (typeName: string) =>
+    isComplex(types[typeName]) ? `${typeName}::metadata()` : `&${generateTypeMetadata(typeName, types[typeName])}`;
+
+  const lookupArms = Object.keys(types).map(
+    (typeName) => `        ${typePrefixes[typeName]}"${typeName}" => Some(${metadataExpression(typeName)}),\n`
+  );
+  const pushes = Object.keys(types).map(
+    (typeName) => `    ${typePrefixes[typeName]}result.push(${metadataExpression(typeName)});\n`
+  );
+
+  return `/// Look up the metadata of an XDR type by its name
+#[allow(dead_code)]
+pub fn type_metadata(name: &str) -> Option<&'static XdrTypeMetadata> {
+    match name {
+${lookupArms.join("")}        _ => None,
+    }
+}
+
+/// Returns the metadata of all XDR types
+#[allow(dead_code)]
+pub fn all_type_metadata() -> Vec<&'static XdrTypeMetadata> {
+    let mut result = Vec::new();
+${pushes.join("")}    result
+}
+
+`;
+}
@@ -0,0 +1,42 @@
+import { mkdirSync, readFileSync, writeFileSync } from "fs";
+import { dirname } from "path";
+
+import { XdrType } from "../types/types";
+
+// The schema of a generated module: the structure of all types without their Rust code
+//
+// Every generation run can store its schema, so that later steps can relate the types of several
+// runs, e.g., of several versions of the Stellar protocol.
+export interface XdrSchema {
+  constants: Record<string, number>;
+  types: Record<string, XdrType>;
+  // the cargo feature of every type, `null` if the type is always generated
+  typeFeatures: Record<string, string | null>;
+  featureDependencies?: Record<string, string[]>;
+}
+
+function stripRustCode(type: XdrType): XdrType {
+  switch (type.type) {
+    case "enum":
+    case "struct":
+    case "union":
+    case "newtype":
+      return { ...type, typeDefinition: "", typeImplementation: "", additionalImplementations: [] };
+    default:
+      return type;
+  }
+}
+
+export function writeSchema(path: string, schema: XdrSchema) {
+  const types: Record<string, XdrType> = {};
+  Object.entries(schema.types).forEach(([typeName, type]) => {
+    types[typeName] = stripRustCode(type);
+  });
+
+  mkdirSync(dirname(path), { recursive: true });
+  writeFileSync(path, JSON.stringify({ ...schema, types }, null, 2) + "\n");
+}
+
+export function readSchema(path: string): XdrSchema {
+  return JSON.parse(readFileSync(path, "utf8"));
+}
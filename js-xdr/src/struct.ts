@@ -4,33 +4,36 @@ import {
   determineFullyQualifiedTypeReference,
   determineTypeReference,
   ReferableXdrType,
+  StructField,
   StructType,
 } from "../types/types";
+import { renderMemberComments, TypeDocumentation } from "./documentation";
 
 export type StructDefinition = Array<[string, ReferableXdrType]>;
 
-export function processStruct(name: string, structDefinition: StructDefinition): StructType {
+export function processStruct(
+  name: string,
+  structDefinition: StructDefinition,
+  documentation?: TypeDocumentation
+): StructType {
   const subTypes: string[] = [];
   const subReaders: string[] = [];
   const subWriters: string[] = [];
+  const fields: StructField[] = [];
   let dependencies: Record<string, true> = {};
 
   structDefinition.forEach((entry) => {
-    const [key, type] = entry;
+    const [key, fieldType] = entry;
     let stringifiedKey = snakeCase(key);
     if (stringifiedKey === "type") stringifiedKey += "_";
-    const isOptionalCycle =
-      type.type === "option" && type.innerType.type === "reference" && type.innerType.name === name;
+    const typeReference = determineTypeReference(fieldType);
+    const fullyQualifiedTypeReference = determineFullyQualifiedTypeReference(fieldType);
+    fields.push({ name: key, identifier: stringifiedKey, fieldType });
 
-    const typeReference = isOptionalCycle ? `Option<Box<${name}>>` : determineTypeReference(type);
-    const fullyQualifiedTypeReference = isOptionalCycle
-      ? `Option::<Box<${name}>>`
-      : determineFullyQualifiedTypeReference(type);
-
-    subTypes.push(`    pub ${stringifiedKey}: ${typeReference}`);
+    subTypes.push(`${renderMemberComments(documentation, [key], "    ")}    pub ${stringifiedKey}: ${typeReference}`);
     subWriters.push(`        self.${stringifiedKey}.to_xdr_buffered(write_stream);`);
     subReaders.push(`            ${stringifiedKey}: ${fullyQualifiedTypeReference}::from_xdr_buffered(read_stream)?,`);
-    dependencies = { ...dependencies, ...determineDependencies(type) };
+    dependencies = { ...dependencies, ...determineDependencies(fieldType) };
   });
 
   const typeDefinition = `pub struct ${name} {\n${subTypes.join(",\n")}\n}`;
@@ -47,5 +50,12 @@ ${subReaders.join("\n")}
         })
     }`;
 
-  return { type: "struct", typeDefinition, typeImplementation, referredTypes: dependencies };
+  return {
+    type: "struct",
+    typeDefinition,
+    typeImplementation,
+    additionalImplementations: [],
+    referredTypes: dependencies,
+    fields,
+  };
 }
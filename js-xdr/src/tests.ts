@@ -0,0 +1,277 @@
+import { snakeCase } from "snake-case";
+import {
+  determineFullyQualifiedTypeReference,
+  determineTypeReference,
+  EnumType,
+  lengthToString,
+  ReferableXdrType,
+  UnionType,
+  XdrType,
+} from "../types/types";
+
+// guards against unforeseen cycles between types that are not broken by an option or a var array
+const MAX_SAMPLE_DEPTH = 64;
+
+type SampleKind = "minimal" | "populated";
+
+// Determine a Rust expression that constructs a sample value of the given type
+//
+// A "minimal" sample uses the first enum case or union arm, `None` for options and empty
+// var arrays, so it never recurses into cyclic types. A "populated" sample uses the last
+// enum case or union arm and fills options and var arrays with minimal samples.
+function determineSampleValue(
+  type: XdrType,
+  kind: SampleKind,
+  types: Record<string, XdrType>,
+  typeName: string,
+  depth: number
+): string {
+  if (depth > MAX_SAMPLE_DEPTH) {
+    throw new Error(`Cannot construct a sample value for type "${typeName}", the type seems to be cyclic`);
+  }
+
+  const populated = kind === "populated";
+  const sampleInner = (innerType: XdrType, innerKind: SampleKind, innerTypeName: string = typeName) =>
+    determineSampleValue(innerType, innerKind, types, innerTypeName, depth + 1);
+
+  switch (type.type) {
+    case "uhyper":
+      return populated ? "u64::MAX" : "0u64";
+    case "hyper":
+      return populated ? "i64::MIN" : "0i64";
+    case "uint":
+      return populated ? "u32::MAX" : "0u32";
+    case "int":
+      return populated ? "i32::MIN" : "0i32";
+    case "bool":
+      return populated ? "true" : "false";
+    case "void":
+      throw new Error("No need to construct a sample of void");
+    case "opaque":
+      return populated ? `[0xabu8; ${lengthToString(type.length)}]` : `[0u8; ${lengthToString(type.length)}]`;
+    case "limitedVarOpaque":
+    case "unlimitedVarOpaque": {
+      const maxLength = type.type === "limitedVarOpaque" ? lengthToString(type.maxLength) : "i32::MAX";
+      const content = populated ? `vec![0xabu8; core::cmp::min(3, ${maxLength} as usize)]` : "Vec::new()";
+      return `${determineFullyQualifiedTypeReference(type)}::new(${content}).unwrap()`;
+    }
+    case "limitedString":
+    case "unlimitedString": {
+      const maxLength = type.type === "limitedString" ? lengthToString(type.maxLength) : "i32::MAX";
+      const content = populated ? `b"stellar"[..core::cmp::min(7, ${maxLength} as usize)].to_vec()` : "Vec::new()";
+      return `${determineFullyQualifiedTypeReference(type)}::new(${content}).unwrap()`;
+    }
+    case "array":
+      return `core::array::from_fn(|_| ${sampleInner(type.innerType, kind)})`;
+    case "limitedVarArray":
+    case "unlimitedVarArray": {
+      const content = populated ? `vec![${sampleInner(type.innerType, "minimal")}]` : "Vec::new()";
+      return `${determineFullyQualifiedTypeReference(type)}::new(${content}).unwrap()`;
+    }
+    case "option":
+      return populated ? `Some(${sampleInner(type.innerType, "minimal")})` : "None";
+    case "box":
+      return `Box::new(${sampleInner(type.innerType, kind)})`;
+    case "reference": {
+      const referredType = types[type.name];
+      if (referredType === undefined) {
+        throw new Error(`Type "${typeName}" refers to the undefined type "${type.name}"`);
+      }
+      return sampleInner(referredType, kind, type.name);
+    }
+    case "enum": {
+      const enumCase = populated ? type.cases[type.cases.length - 1] : type.cases[0];
+      return `${typeName}::${enumCase.identifier}`;
+    }
+    case "struct": {
+      const fields = type.fields.map(
+        ({ identifier, fieldType }) => `${identifier}: ${sampleInner(fieldType, kind)}`
+      );
+      return `${typeName} { ${fields.join(", ")} }`;
+    }
+    case "newtype":
+      return `${typeName}(${sampleInner(type.innerType, kind)})`;
+    case "union": {
+      const arm = populated ? type.arms[type.arms.length - 1] : type.arms[0];
+      if (arm === undefined) return determineUnionDefaultSample(typeName, type, types);
+      return arm.armType === undefined
+        ? `${typeName}::${arm.caseIdentifier}`
+        : `${typeName}::${arm.caseIdentifier}(${sampleInner(arm.armType, kind)})`;
+    }
+  }
+}
+
+// Determine a value that is not a case of an enum, used as sample for the case `Unknown` of open enums
+function determineUnknownEnumValue(type: EnumType): number {
+  return Math.max(...type.cases.map((enumCase) => enumCase.value)) + 1;
+}
+
+// Determine a sample value for the `Default` arm of a union, i.e., a discriminant that is not covered
+// by any of the other arms
+function determineUnionDefaultSample(typeName: string, type: UnionType, types: Record<string, XdrType>): string {
+  const usedSwitchValues = type.arms.map((arm) => arm.switchValue);
+  const switchType: ReferableXdrType | XdrType =
+    type.switchOn.type === "reference" ? types[type.switchOn.name] : type.switchOn;
+
+  switch (switchType.type) {
+    case "enum": {
+      const unusedCase = switchType.cases.find((enumCase) => usedSwitchValues.indexOf(enumCase.name) === -1);
+      const switchTypeReference = determineTypeReference(type.switchOn);
+      if (unusedCase !== undefined) return `${typeName}::Default(${switchTypeReference}::${unusedCase.identifier})`;
+      if (!switchType.open) break;
+      return `${typeName}::Default(${switchTypeReference}::Unknown(${determineUnknownEnumValue(switchType)}))`;
+    }
+    case "bool":
+      return `${typeName}::Default(${usedSwitchValues.indexOf(1) === -1 ? "true" : "false"})`;
+    case "int":
+    case "uint": {
+      let unusedValue = 0;
+      while (usedSwitchValues.indexOf(unusedValue) !== -1) unusedValue++;
+      return `${typeName}::Default(${unusedValue})`;
+    }
+  }
+
+  throw new Error(`Cannot determine a discriminant for the default arm of union "${typeName}"`);
+}
+
+// Determine Rust expressions for sample values of a type
+//
+// The samples cover every enum case and every union arm of the type
+export function determineSampleValues(typeName: string, types: Record<string, XdrType>): string[] {
+  const type = types[typeName];
+
+  switch (type.type) {
+    case "enum": {
+      const samples = type.cases.map((enumCase) => `${typeName}::${enumCase.identifier}`);
+      if (type.open) samples.push(`${typeName}::Unknown(${determineUnknownEnumValue(type)})`);
+      return samples;
+    }
+    case "union": {
+      const samples: string[] = [];
+      type.arms.forEach((arm) => {
+        if (arm.armType === undefined) {
+          samples.push(`${typeName}::${arm.caseIdentifier}`);
+        } else {
+          samples.push(
+            `${typeName}::${arm.caseIdentifier}(${determineSampleValue(arm.armType, "minimal", types, typeName, 0)})`
+          );
+          samples.push(
+            `${typeName}::${arm.caseIdentifier}(${determineSampleValue(arm.armType, "populated", types, typeName, 0)})`
+          );
+        }
+      });
+      if (type.hasDefaultArm) {
+        samples.push(determineUnionDefaultSample(typeName, type, types));
+      }
+      return samples;
+    }
+    default:
+      return [
+        determineSampleValue(type, "minimal", types, typeName, 0),
+        determineSampleValue(type, "populated", types, typeName, 0),
+      ];
+  }
+}
+
+// Generate a test that checks the conversions of an enum from and to integers and names
+function generateEnumConversionTest(typeName: string, type: EnumType, typePrefix: string): string {
+  // unknown values of open enums are converted from and to their integer value
+  const unknownValue = determineUnknownEnumValue(type);
+  const unknownValueCheck = type.open
+    ? `
+        let unknown = ${typeName}::Unknown(${unknownValue});
+        assert_eq!(${typeName}::try_from(${unknownValue}), Ok(unknown));
+        assert_eq!(i32::from(unknown), ${unknownValue});
+        assert_eq!("${unknownValue}".parse::<${typeName}>(), Ok(unknown));
+        assert_eq!(std::format!("{}", unknown), "${unknownValue}");`
+    : "";
+  // decoding never yields `Unknown` with a defined value
+  const normalizationCheck = type.open
+    ? `
+            let unnormalized = ${typeName}::Unknown(i32::from(*value));
+            assert_ne!(unnormalized, *value);
+            assert_eq!(${typeName}::from_xdr(unnormalized.to_xdr()).unwrap(), *value);`
+    : "";
+
+  return `
+    ${typePrefix}#[test]
+    fn enum_conversions_${snakeCase(typeName)}() {
+        for value in ${typeName}::variants() {
+            assert_eq!(${typeName}::try_from(i32::from(*value)), Ok(*value));
+            assert_eq!(value.name().parse::<${typeName}>(), Ok(*value));
+            assert_eq!(std::format!("{}", value), value.name());${normalizationCheck}
+        }${unknownValueCheck}
+    }
+`;
+}
+
+// Generate a test module that checks that sample values of every type survive an encoding
+// round trip, also through the dynamic decoder if it uses the schema of these types, and that their
+// encoding is byte stable
+export function generateTestModule(
+  types: Record<string, XdrType>,
+  typePrefixes: Record<string, string>,
+  dynamicRoundTrip: boolean
+): string {
+  const tests = Object.keys(types).map((typeName) => {
+    const type = types[typeName];
+    const samples = determineSampleValues(typeName, types)
+      .map((sample) => `            ${sample},`)
+      .join("\n");
+
+    // the XDR encoding of a union starts with its discriminant
+    const discriminantCheck =
+      type.type === "union"
+        ? "\n            assert_eq!(sample.discriminant().to_xdr()[..], sample.to_xdr()[..4]);"
+        : "";
+    const dynamicCheck = dynamicRoundTrip
+      ? `\n            #[cfg(feature = "dynamic")]\n            assert_dynamic_round_trip("${typeName}", sample);`
+      : "";
+
+    return `    ${typePrefixes[typeName]}#[test]
+    fn round_trip_${snakeCase(typeName)}() {
+        let samples: Vec<${typeName}> = vec![
+${samples}
+        ];
+        for sample in samples.iter() {
+            assert_round_trip(sample);${discriminantCheck}${dynamicCheck}
+        }
+    }
+${type.type === "enum" ? generateEnumConversionTest(typeName, type, typePrefixes[typeName]) : ""}`;
+  });
+
+  const dynamicRoundTripHelper = dynamicRoundTrip
+    ? `    #[cfg(feature = "dynamic")]
+    fn assert_dynamic_round_trip<T: XdrCodec>(type_name: &str, value: &T) {
+        let encoded = value.to_xdr();
+        let decoded = crate::dynamic::decode_dynamic(type_name, &encoded).unwrap();
+        assert_eq!(crate::dynamic::encode_dynamic(type_name, &decoded).unwrap(), encoded);
+    }
+
+`
+    : "";
+
+  return `#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::{vec, vec::Vec};
+
+    fn assert_round_trip<T: XdrCodec + PartialEq + core::fmt::Debug>(value: &T) {
+        let encoded = value.to_xdr();
+        let decoded = T::from_xdr(&encoded).unwrap();
+        assert_eq!(&decoded, value);
+        assert_eq!(decoded.to_xdr(), encoded);
+    }
+
+${dynamicRoundTripHelper}    #[test]
+    fn type_metadata_lookup() {
+        for metadata in all_type_metadata() {
+            assert_eq!(type_metadata(metadata.name), Some(metadata));
+        }
+    }
+
+${tests.join("\n")}}
+`;
+}
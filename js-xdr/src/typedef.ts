@@ -0,0 +1,61 @@
+import {
+  determineDependencies,
+  determineFullyQualifiedTypeReference,
+  determineTypeReference,
+  NewtypeType,
+  ReferableXdrType,
+} from "../types/types";
+
+// Process a typedef as a newtype wrapping the aliased type instead of a mere type alias
+//
+// This way the type checker distinguishes typedefs of the same type, e.g., `Hash` and `Uint256`
+export function processTypedef(name: string, innerType: ReferableXdrType): NewtypeType {
+  const typeReference = determineTypeReference(innerType);
+  const fullyQualifiedTypeReference = determineFullyQualifiedTypeReference(innerType);
+
+  const typeDefinition = `pub struct ${name}(pub ${typeReference});`;
+  const typeImplementation = `
+    fn to_xdr_buffered(&self, write_stream: &mut WriteStream) {
+        self.0.to_xdr_buffered(write_stream);
+    }
+
+    fn from_xdr_buffered<T: AsRef<[u8]>>(
+        read_stream: &mut ReadStream<T>,
+    ) -> Result<Self, DecodeError> {
+        Ok(${name}(${fullyQualifiedTypeReference}::from_xdr_buffered(read_stream)?))
+    }`;
+
+  const additionalImplementations = [
+    `impl Deref for ${name} {
+    type Target = ${typeReference};
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}`,
+    `impl DerefMut for ${name} {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}`,
+    `impl From<${typeReference}> for ${name} {
+    fn from(value: ${typeReference}) -> Self {
+        ${name}(value)
+    }
+}`,
+    `impl From<${name}> for ${typeReference} {
+    fn from(value: ${name}) -> Self {
+        value.0
+    }
+}`,
+  ];
+
+  return {
+    type: "newtype",
+    typeDefinition,
+    typeImplementation,
+    additionalImplementations,
+    referredTypes: determineDependencies(innerType),
+    innerType,
+  };
+}
@@ -1,4 +1,5 @@
 import { pascalCase } from "pascal-case";
+import { snakeCase } from "snake-case";
 import {
   BoolType,
   determineDependencies,
@@ -8,10 +9,12 @@ import {
   ReferableXdrType,
   ReferenceType,
   UIntType,
+  UnionArm,
   UnionType,
   VoidType,
   XdrType,
 } from "../types/types";
+import { renderMemberComments, TypeDocumentation } from "./documentation";
 
 export type UnionDefinition = {
   switchOn: IntType | UIntType | BoolType | ReferenceType;
@@ -21,10 +24,60 @@ export type UnionDefinition = {
   defaultArm?: VoidType;
 };
 
-export function processUnion(name: string, unionDefinition: UnionDefinition, resolvedSwitchType: XdrType): UnionType {
+function generateIsAccessor(name: string, caseIdentifier: string, accessorName: string, hasValue: boolean): string {
+  return `
+    /// Returns \`true\` if this union is of the arm \`${name}::${caseIdentifier}\`
+    pub fn is_${accessorName}(&self) -> bool {
+        matches!(self, ${name}::${caseIdentifier}${hasValue ? "(_)" : ""})
+    }
+`;
+}
+
+function generateArmAccessors(
+  name: string,
+  caseIdentifier: string,
+  accessorName: string,
+  typeReference: string
+): string {
+  return `${generateIsAccessor(name, caseIdentifier, accessorName, true)}
+    /// Returns a reference to the value of the arm \`${name}::${caseIdentifier}\`, if this union is of this arm
+    pub fn as_${accessorName}(&self) -> Option<&${typeReference}> {
+        match self {
+            ${name}::${caseIdentifier}(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value of the arm \`${name}::${caseIdentifier}\`, if this union is of this arm
+    pub fn as_${accessorName}_mut(&mut self) -> Option<&mut ${typeReference}> {
+        match self {
+            ${name}::${caseIdentifier}(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the arm \`${name}::${caseIdentifier}\`, if this union is of this arm
+    pub fn into_${accessorName}(self) -> Option<${typeReference}> {
+        match self {
+            ${name}::${caseIdentifier}(value) => Some(value),
+            _ => None,
+        }
+    }
+`;
+}
+
+export function processUnion(
+  name: string,
+  unionDefinition: UnionDefinition,
+  resolvedSwitchType: XdrType,
+  documentation?: TypeDocumentation
+): UnionType {
   const subTypes: string[] = [];
   const subReaders: string[] = [];
   const subWriters: string[] = [];
+  const arms: UnionArm[] = [];
+  const discriminantReaders: string[] = [];
+  const accessors: string[] = [];
   let defaultReader: undefined | string;
 
   unionDefinition.switches.forEach((switchSpec) => {
@@ -42,31 +95,49 @@ export function processUnion(name: string, unionDefinition: UnionDefinition, res
         ? `${switchValue}`
         : `${determineTypeReference(unionDefinition.switchOn)}::${caseIdentifier}`;
 
+    const accessorName = snakeCase(caseIdentifier);
+
+    let caseName = String(switchValue);
+    let discriminant = Number(switchValue);
+    if (typeof switchValue === "string") {
+      const enumCase =
+        resolvedSwitchType.type === "enum"
+          ? resolvedSwitchType.cases.find((candidate) => candidate.name === switchValue)
+          : undefined;
+      if (enumCase === undefined) {
+        throw new Error(`Union definition "${name}" has a switch "${switchValue}" that is not an enum case`);
+      }
+      caseName = enumCase.xdrName;
+      discriminant = enumCase.value;
+    }
+
     if (typeof armOrVoid === "string") {
-      const type: ReferableXdrType = unionDefinition.arms[armOrVoid];
-      if (type === undefined) {
+      const armType: ReferableXdrType = unionDefinition.arms[armOrVoid];
+      if (armType === undefined) {
         throw new Error(`Union definition "${name}" has a switch "${caseIdentifier}" without an arm definition`);
       }
 
-      const isOptionalCycle =
-        type.type === "option" && type.innerType.type === "reference" && type.innerType.name === name;
-
-      const typeReference = isOptionalCycle ? `Option<Box<${name}>>` : determineTypeReference(type);
-      const fullyQualifiedTypeReference = isOptionalCycle
-        ? `Option::<Box<${name}>>`
-        : determineFullyQualifiedTypeReference(type);
+      const typeReference = determineTypeReference(armType);
+      const fullyQualifiedTypeReference = determineFullyQualifiedTypeReference(armType);
+      arms.push({ switchValue, caseIdentifier, caseName, discriminant, armName: armOrVoid, armType });
 
-      subTypes.push(`    ${caseIdentifier}(${typeReference})`);
+      const comments = renderMemberComments(documentation, [String(switchValue), armOrVoid], "    ");
+      subTypes.push(`${comments}    ${caseIdentifier}(${typeReference})`);
       subWriters.push(
         `            ${name}::${caseIdentifier}(value) => {${fieldName}.to_xdr_buffered(write_stream); value.to_xdr_buffered(write_stream)},`
       );
       subReaders.push(
         `            ${simpleFieldName} => Ok(${name}::${caseIdentifier}(${fullyQualifiedTypeReference}::from_xdr_buffered(read_stream)?)),`
       );
+      discriminantReaders.push(`            ${name}::${caseIdentifier}(_) => ${simpleFieldName},`);
+      accessors.push(generateArmAccessors(name, caseIdentifier, accessorName, typeReference));
     } else {
-      subTypes.push(`    ${caseIdentifier}`);
+      arms.push({ switchValue, caseIdentifier, caseName, discriminant });
+      subTypes.push(`${renderMemberComments(documentation, [String(switchValue)], "    ")}    ${caseIdentifier}`);
       subWriters.push(`            ${name}::${caseIdentifier} => ${fieldName}.to_xdr_buffered(write_stream),`);
       subReaders.push(`            ${simpleFieldName} => Ok(${name}::${caseIdentifier}),`);
+      discriminantReaders.push(`            ${name}::${caseIdentifier} => ${simpleFieldName},`);
+      accessors.push(generateIsAccessor(name, caseIdentifier, accessorName, false));
     }
   });
 
@@ -74,12 +145,16 @@ export function processUnion(name: string, unionDefinition: UnionDefinition, res
     resolvedSwitchType.type === "uint" ||
     resolvedSwitchType.type === "int" ||
     (resolvedSwitchType.type === "bool" && unionDefinition.switches.length < 2) ||
-    (resolvedSwitchType.type === "enum" && unionDefinition.switches.length < resolvedSwitchType.noOfCases);
+    (resolvedSwitchType.type === "enum" &&
+      (resolvedSwitchType.open || unionDefinition.switches.length < resolvedSwitchType.noOfCases));
 
-  if (unionDefinition.defaultArm || patternNotExaustive) {
+  const hasDefaultArm = unionDefinition.defaultArm !== undefined || patternNotExaustive;
+  if (hasDefaultArm) {
     subTypes.push(`    Default(${determineTypeReference(unionDefinition.switchOn)})`);
     subWriters.push(`            ${name}::Default(code) => code.to_xdr_buffered(write_stream),`);
     defaultReader = `${name}::Default(code)`;
+    discriminantReaders.push(`            ${name}::Default(code) => *code,`);
+    accessors.push(generateIsAccessor(name, "Default", "default", true));
   }
 
   let dependencies: Record<string, true> = {};
@@ -104,5 +179,25 @@ ${subReaders.join("\n")}${defaultReader ? `\n            code => Ok(${defaultRea
         }
     }`;
 
-  return { type: "union", typeDefinition, typeImplementation, referredTypes: dependencies };
+  return {
+    type: "union",
+    typeDefinition,
+    typeImplementation,
+    additionalImplementations: [
+      `#[allow(unreachable_patterns)]
+impl ${name} {
+    /// Returns the discriminant of this union
+    pub fn discriminant(&self) -> ${determineTypeReference(unionDefinition.switchOn)} {
+        match self {
+${discriminantReaders.join("\n")}
+        }
+    }
+${accessors.join("")}}`,
+    ],
+    referredTypes: dependencies,
+    switchOn: unionDefinition.switchOn,
+    switchName: unionDefinition.switchName,
+    arms,
+    hasDefaultArm,
+  };
 }
@@ -0,0 +1,386 @@
+import { mkdirSync, writeFileSync } from "fs";
+import { join } from "path";
+import { snakeCase } from "snake-case";
+
+import { ReferenceType, XdrType } from "../types/types";
+import { assertAcyclicFeatures } from "./features";
+import { readSchema, XdrSchema } from "./schema";
+import { determineSampleValues } from "./tests";
+import { writeCargoFeatures } from "./writer";
+
+// The final step when generating one module per version of the Stellar protocol, e.g., `xdr::curr`
+// and `xdr::next`
+//
+// Every version has been generated into its own module `src/xdr/<version>.rs` and has stored its
+// schema. This step generates
+// - the module `xdr` that declares the version modules and re-exports the first version
+// - conversions between the types of the versions: `From` for types whose structure did not change;
+//   types that changed get no conversion
+// - tests that convert sample values of every convertible type and compare their XDR encodings
+// - the cargo features of all versions
+
+interface Version {
+  module: string;
+  schema: XdrSchema;
+}
+
+function resolveLength(length: number | ReferenceType, schema: XdrSchema): number {
+  if (typeof length === "number") return length;
+
+  const value = schema.constants[length.name];
+  if (value === undefined) throw new Error(`The constant "${length.name}" is not defined`);
+  return value;
+}
+
+// Determine whether two types have the same structure: the same XDR encoding and the same names of
+// referred types, fields, enum cases and union arms, so that every value of one type corresponds to
+// exactly one value of the other type
+//
+// Pairs of types that are currently compared are assumed to be identical, so that cyclic types terminate.
+function areIdentical(
+  typeA: XdrType,
+  typeB: XdrType,
+  versionA: Version,
+  versionB: Version,
+  assumedPairs: Record<string, true>
+): boolean {
+  const identical = (innerA: XdrType, innerB: XdrType) =>
+    areIdentical(innerA, innerB, versionA, versionB, assumedPairs);
+  const sameLength = (lengthA: number | ReferenceType, lengthB: number | ReferenceType) =>
+    resolveLength(lengthA, versionA.schema) === resolveLength(lengthB, versionB.schema);
+
+  switch (typeA.type) {
+    case "uhyper":
+    case "hyper":
+    case "uint":
+    case "int":
+    case "bool":
+    case "void":
+    case "unlimitedVarOpaque":
+    case "unlimitedString":
+      return typeA.type === typeB.type;
+    case "limitedVarOpaque":
+      return typeB.type === "limitedVarOpaque" && sameLength(typeA.maxLength, typeB.maxLength);
+    case "limitedString":
+      return typeB.type === "limitedString" && sameLength(typeA.maxLength, typeB.maxLength);
+    case "opaque":
+      return typeB.type === "opaque" && sameLength(typeA.length, typeB.length);
+    case "array":
+      return (
+        typeB.type === "array" && sameLength(typeA.length, typeB.length) && identical(typeA.innerType, typeB.innerType)
+      );
+    case "limitedVarArray":
+      return (
+        typeB.type === "limitedVarArray" &&
+        sameLength(typeA.maxLength, typeB.maxLength) &&
+        identical(typeA.innerType, typeB.innerType)
+      );
+    case "unlimitedVarArray":
+      return typeB.type === "unlimitedVarArray" && identical(typeA.innerType, typeB.innerType);
+    case "option":
+      return typeB.type === "option" && identical(typeA.innerType, typeB.innerType);
+    case "box":
+      return typeB.type === "box" && identical(typeA.innerType, typeB.innerType);
+    case "newtype":
+      return typeB.type === "newtype" && identical(typeA.innerType, typeB.innerType);
+    case "reference": {
+      if (typeB.type !== "reference" || typeA.name !== typeB.name) return false;
+      const pair = `${typeA.name} ${typeB.name}`;
+      if (assumedPairs[pair]) return true;
+      assumedPairs[pair] = true;
+      const result = identical(versionA.schema.types[typeA.name], versionB.schema.types[typeB.name]);
+      delete assumedPairs[pair];
+      return result;
+    }
+    case "enum":
+      return (
+        typeB.type === "enum" &&
+        typeA.open === typeB.open &&
+        typeA.cases.length === typeB.cases.length &&
+        typeA.cases.every(
+          (enumCase, index) => enumCase.name === typeB.cases[index].name && enumCase.value === typeB.cases[index].value
+        )
+      );
+    case "struct":
+      return (
+        typeB.type === "struct" &&
+        typeA.fields.length === typeB.fields.length &&
+        typeA.fields.every(
+          (field, index) =>
+            field.name === typeB.fields[index].name && identical(field.fieldType, typeB.fields[index].fieldType)
+        )
+      );
+    case "union":
+      return (
+        typeB.type === "union" &&
+        typeA.hasDefaultArm === typeB.hasDefaultArm &&
+        identical(typeA.switchOn, typeB.switchOn) &&
+        typeA.arms.length === typeB.arms.length &&
+        typeA.arms.every((arm, index) => {
+          const armB = typeB.arms[index];
+          if (arm.discriminant !== armB.discriminant || arm.caseIdentifier !== armB.caseIdentifier) return false;
+          if (arm.armType === undefined || armB.armType === undefined) return arm.armType === armB.armType;
+          return identical(arm.armType, armB.armType);
+        })
+      );
+  }
+}
+
+function determineCfg(features: Array<string | null>): string {
+  const requiredFeatures = features.filter(
+    (feature, index): feature is string => feature !== null && features.indexOf(feature) === index
+  );
+
+  if (requiredFeatures.length === 0) return "";
+  if (requiredFeatures.length === 1) return `#[cfg(feature = "${requiredFeatures[0]}")]\n`;
+  return `#[cfg(all(${requiredFeatures.map((feature) => `feature = "${feature}"`).join(", ")}))]\n`;
+}
+
+// Typedefs are not converted as they are mere type aliases, unless they are generated as newtypes
+function isConvertible(type: XdrType | undefined): boolean {
+  return (
+    type !== undefined &&
+    (type.type === "enum" || type.type === "struct" || type.type === "union" || type.type === "newtype")
+  );
+}
+
+function needsConversion(type: XdrType, version: Version): boolean {
+  switch (type.type) {
+    case "reference": {
+      const referredType = version.schema.types[type.name];
+      return isConvertible(referredType) || needsConversion(referredType, version);
+    }
+    case "array":
+    case "limitedVarArray":
+    case "unlimitedVarArray":
+    case "option":
+    case "box":
+      return needsConversion(type.innerType, version);
+    default:
+      return false;
+  }
+}
+
+// Render the conversion of the Rust expression `expression` of the type `type` into the identical type of
+// the other version
+//
+// Types without generated code, e.g., integers or `LimitedString<N>`, are the same Rust type in both versions.
+function renderConversion(expression: string, type: XdrType, version: Version): string {
+  if (!needsConversion(type, version)) return expression;
+
+  switch (type.type) {
+    case "reference": {
+      const referredType = version.schema.types[type.name];
+      return isConvertible(referredType) ? `${expression}.into()` : renderConversion(expression, referredType, version);
+    }
+    case "array":
+      return `${expression}.map(|element| ${renderConversion("element", type.innerType, version)})`;
+    case "limitedVarArray":
+    case "unlimitedVarArray":
+      return `${expression}.map_elements(|element| ${renderConversion("element", type.innerType, version)})`;
+    case "option":
+      return `${expression}.map(|value| ${renderConversion("value", type.innerType, version)})`;
+    case "box":
+      return `Box::new(${renderConversion(`(*${expression})`, type.innerType, version)})`;
+    default:
+      return expression;
+  }
+}
+
+function renderConversionBody(type: XdrType, source: string, target: string, from: Version): string {
+  switch (type.type) {
+    case "enum": {
+      const cases = type.cases.map(
+        (enumCase) => `            ${source}::${enumCase.identifier} => ${target}::${enumCase.identifier},\n`
+      );
+      if (type.open) cases.push(`            ${source}::Unknown(value) => ${target}::Unknown(value),\n`);
+      return `        match value {\n${cases.join("")}        }`;
+    }
+    case "struct": {
+      const fields = type.fields.map(
+        (field) =>
+          `            ${field.identifier}: ${renderConversion(`value.${field.identifier}`, field.fieldType, from)},\n`
+      );
+      return `        ${target} {\n${fields.join("")}        }`;
+    }
+    case "union": {
+      const arms = type.arms.map((arm) => {
+        if (arm.armType === undefined) {
+          return `            ${source}::${arm.caseIdentifier} => ${target}::${arm.caseIdentifier},\n`;
+        }
+        const { caseIdentifier } = arm;
+        const armValue = renderConversion("value", arm.armType, from);
+        return `            ${source}::${caseIdentifier}(value) => ${target}::${caseIdentifier}(${armValue}),\n`;
+      });
+      if (type.hasDefaultArm) {
+        const code = renderConversion("code", type.switchOn, from);
+        arms.push(`            ${source}::Default(code) => ${target}::Default(${code}),\n`);
+      }
+      return `        match value {\n${arms.join("")}        }`;
+    }
+    case "newtype":
+      return `        ${target}(${renderConversion("value.0", type.innerType, from)})`;
+    default:
+      throw new Error(`The type "${source}" cannot be converted`);
+  }
+}
+
+// Generate a `From` conversion of a type whose structure did not change
+//
+// The conversion maps every field, enum case and union arm to its counterpart, so that it cannot fail.
+function generateConversion(typeName: string, from: Version, to: Version): string {
+  const cfg = determineCfg([from.schema.typeFeatures[typeName], to.schema.typeFeatures[typeName]]);
+  const source = `${from.module}::${typeName}`;
+  const target = `${to.module}::${typeName}`;
+  const body = renderConversionBody(from.schema.types[typeName], source, target, from);
+
+  return `${cfg}impl From<${source}> for ${target} {
+    fn from(value: ${source}) -> Self {
+${body}
+    }
+}
+
+`;
+}
+
+// Determine the names of the types that are converted between two versions
+//
+// Types whose structure changed between the versions get no conversion.
+function determineConvertibleTypes(versionA: Version, versionB: Version): string[] {
+  return Object.keys(versionA.schema.types).filter((typeName) => {
+    const typeA = versionA.schema.types[typeName];
+    const typeB = versionB.schema.types[typeName];
+    return isConvertible(typeA) && isConvertible(typeB) && areIdentical(typeA, typeB, versionA, versionB, {});
+  });
+}
+
+// Generate the conversions between the types of two versions
+function generateConversions(versionA: Version, versionB: Version): string {
+  return determineConvertibleTypes(versionA, versionB)
+    .map(
+      (typeName) => generateConversion(typeName, versionA, versionB) + generateConversion(typeName, versionB, versionA)
+    )
+    .join("");
+}
+
+// Generate a test module that converts sample values of every converted type into the other version
+// and back, and checks that neither the value nor its encoding changes
+function generateConversionTests(versionA: Version, versionB: Version): string {
+  const tests = determineConvertibleTypes(versionA, versionB).map((typeName) => {
+    const cfg = determineCfg([versionA.schema.typeFeatures[typeName], versionB.schema.typeFeatures[typeName]]);
+    const samples = determineSampleValues(typeName, versionA.schema.types)
+      .map((sample) => `            ${sample},\n`)
+      .join("");
+
+    return `    ${cfg}#[test]
+    fn convert_${snakeCase(typeName)}() {
+        let samples: Vec<${typeName}> = vec![
+${samples}        ];
+        for sample in samples {
+            assert_conversion::<${typeName}, ${versionB.module}::${typeName}>(sample);
+        }
+    }
+`;
+  });
+
+  return `#[cfg(test)]
+mod tests_${versionA.module}_${versionB.module} {
+    extern crate std;
+
+    use super::${versionA.module}::*;
+    use super::${versionB.module};
+    #[allow(unused_imports)]
+    use crate::compound_types::{LimitedVarOpaque, LimitedString, LimitedVarArray, UnlimitedVarOpaque, UnlimitedString, UnlimitedVarArray};
+    use crate::xdr_codec::XdrCodec;
+    #[allow(unused_imports)]
+    use sp_std::boxed::Box;
+    use std::{vec, vec::Vec};
+
+    #[allow(dead_code)]
+    fn assert_conversion<A, B>(value: A)
+    where
+        A: XdrCodec + Clone + PartialEq + core::fmt::Debug + From<B>,
+        B: XdrCodec + From<A>,
+    {
+        let converted = B::from(value.clone());
+        assert_eq!(converted.to_xdr(), value.to_xdr());
+        assert_eq!(A::from(converted), value);
+    }
+
+${tests.join("\n")}}
+
+`;
+}
+
+function mergeFeatureDependencies(versions: Version[]): Record<string, string[]> | undefined {
+  const featureDependencies: Record<string, string[]> = {};
+  let hasFeatures = false;
+
+  versions.forEach(({ schema }) => {
+    if (schema.featureDependencies === undefined) return;
+    hasFeatures = true;
+    Object.entries(schema.featureDependencies).forEach(([feature, dependencies]) => {
+      const merged = featureDependencies[feature] || [];
+      dependencies.forEach((dependency) => {
+        if (merged.indexOf(dependency) === -1) merged.push(dependency);
+      });
+      featureDependencies[feature] = merged.sort();
+    });
+  });
+
+  return hasFeatures ? featureDependencies : undefined;
+}
+
+export function generateVersions(outputPath: string, modules: string[], schemaDirectory: string) {
+  const versions = modules.map((module) => ({ module, schema: readSchema(join(schemaDirectory, `${module}.json`)) }));
+
+  const xdrModule =
+    `//! Autogenerated XDR types\n//!\n` +
+    `//! The types of every version of the Stellar protocol are in their own module: ${modules
+      .map((module) => `\`${module}\``)
+      .join(", ")}.\n` +
+    `//! The types of the module \`${modules[0]}\` are re-exported here.\n` +
+    `// This code has been automatically generated\n` +
+    `// using the project https://github.com/pendulum-chain/substrate-stellar-xdr-generator\n` +
+    "// Do not edit this code by hand!\n\n" +
+    modules.map((module) => `pub mod ${module};\n`).join("") +
+    "mod conversions;\n\n" +
+    `pub use ${modules[0]}::*;\n`;
+
+  let conversions =
+    "//! Autogenerated conversions between the types of the versions of the Stellar protocol\n" +
+    "// Do not edit this code by hand!\n\n" +
+    "#[allow(unused_imports)]\nuse sp_std::boxed::Box;\n" +
+    `#[allow(unused_imports)]\nuse super::{${modules.join(", ")}};\n\n`;
+  versions.forEach((versionA, indexA) => {
+    versions.slice(indexA + 1).forEach((versionB) => {
+      conversions += generateConversions(versionA, versionB);
+      conversions += generateConversionTests(versionA, versionB);
+    });
+  });
+
+  mkdirSync(join(outputPath, "src/xdr"), { recursive: true });
+  writeFileSync(join(outputPath, "src/xdr.rs"), xdrModule);
+  writeFileSync(join(outputPath, "src/xdr/conversions.rs"), conversions);
+
+  const featureDependencies = mergeFeatureDependencies(versions);
+  if (featureDependencies !== undefined) {
+    assertAcyclicFeatures(featureDependencies);
+    writeCargoFeatures(outputPath, featureDependencies);
+  }
+}
+
+if (require.main === module) {
+  const { DESTINATION, VERSION_MODULES, SCHEMA_DIRECTORY } = process.env;
+  if (DESTINATION === undefined || VERSION_MODULES === undefined || SCHEMA_DIRECTORY === undefined) {
+    throw new Error(
+      'The environment variables "DESTINATION", "VERSION_MODULES" and "SCHEMA_DIRECTORY" need to be defined'
+    );
+  }
+
+  generateVersions(
+    DESTINATION,
+    VERSION_MODULES.split(",").map((module) => module.trim()),
+    SCHEMA_DIRECTORY
+  );
+}
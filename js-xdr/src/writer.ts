@@ -1,9 +1,14 @@
 import { constantCase } from "change-case";
 
-import { writeFileSync, copyFileSync, mkdirSync } from "fs";
+import { writeFileSync, copyFileSync, mkdirSync, readFileSync } from "fs";
 import { dirname, join } from "path";
 
 import { determineDependencies, determineTypeReference, XdrType } from "../types/types";
+import { renderSource, TypeDocumentation } from "./documentation";
+import { generateSchemaLookup } from "./dynamic";
+import { FeatureAssignment, renderCargoFeatures } from "./features";
+import { generateMetadataLookup, generateXdrTypeImplementation } from "./metadata";
+import { generateTestModule } from "./tests";
 
 export function initializeOutputPath(outputPath: string) {
   mkdirSync(outputPath, { recursive: true });
@@ -31,37 +36,96 @@ function determineMainTypes(types: Record<string, XdrType>) {
   return mainTypes;
 }
 
+// Render the checksums of the input files for the header of the generated code
+//
+// The header deliberately contains no date, so that the same input always produces the same code
+function renderXdrChecksums(xdrChecksums: Array<[string, string]>): string {
+  if (xdrChecksums.length === 0) return "";
+
+  return (
+    "// from the XDR files (SHA-256)\n" +
+    xdrChecksums.map(([fileName, checksum]) => `//   ${checksum}  ${fileName}\n`).join("")
+  );
+}
+
+// Render the generator options that differ from the defaults for the header of the generated code
+//
+// `check-generated` reads them from the header to regenerate the crate with the same options.
+function renderGeneratorOptions(generatorOptions: string[]): string {
+  if (generatorOptions.length === 0) return "";
+
+  return `// generator options: ${generatorOptions.join(" ")}\n`;
+}
+
+// Determine the cargo feature of every type, `null` if the type is always generated
+export function determineTypeFeatures(
+  types: Record<string, XdrType>,
+  featureAssignment: FeatureAssignment | undefined
+): Record<string, string | null> {
+  const mainTypes = determineMainTypes(types);
+  const typeFeatures: Record<string, string | null> = {};
+
+  Object.keys(types).forEach((typeName) => {
+    const feature = featureAssignment === undefined ? "all-types" : featureAssignment.typeFeatures[typeName];
+    typeFeatures[typeName] = mainTypes.has(typeName) ? null : feature;
+  });
+
+  return typeFeatures;
+}
+
 export function generateXdrDefinition(
   types: Record<string, XdrType>,
   constants: Record<string, number>,
+  documentation: Record<string, TypeDocumentation>,
+  typeFeatures: Record<string, string | null>,
+  xdrChecksums: Array<[string, string]>,
+  generatorOptions: string[],
   outputPath: string
 ) {
-  const mainTypes = determineMainTypes(types);
-
   let result =
     `//! Autogenerated XDR types\n` +
-    `// This code has been automatically generated on ${new Date().toISOString().slice(0, 10)}\n` +
+    `// This code has been automatically generated\n` +
     `// using the project https://github.com/pendulum-chain/substrate-stellar-xdr-generator\n` +
+    renderXdrChecksums(xdrChecksums) +
+    renderGeneratorOptions(generatorOptions) +
     "// Do not edit this code by hand!\n\n" +
     "#[allow(unused_imports)]\nuse sp_std::{prelude::*, boxed::Box};\n#[allow(unused_imports)]\nuse core::convert::AsRef;\n#[allow(unused_imports)]\nuse crate::xdr_codec::XdrCodec;\n";
+  result += "#[allow(unused_imports)]\nuse core::{convert::TryFrom, fmt, ops::{Deref, DerefMut}, str::FromStr};\n";
   result += "#[allow(unused_imports)]\nuse crate::streams::{ReadStream, DecodeError, WriteStream};\n";
+  result += "#[allow(unused_imports)]\nuse crate::enums::{InvalidEnumValue, UnknownEnumName};\n";
+  result +=
+    "#[allow(unused_imports)]\nuse crate::type_metadata::{EnumCaseMetadata, UnionArmMetadata, XdrType, XdrTypeKind, XdrTypeMetadata};\n";
+  result +=
+    '#[cfg(feature = "dynamic")]\n#[allow(unused_imports)]\nuse crate::dynamic::{SchemaDefinition, SchemaType, SchemaUnionArm};\n';
   result +=
     "#[allow(unused_imports)]\nuse crate::compound_types::{LimitedVarOpaque, LimitedString, LimitedVarArray, UnlimitedVarOpaque, UnlimitedString, UnlimitedVarArray};\n\n";
 
   result +=
     Object.entries(constants)
       .map(([constant, value]) => {
-        const comment = `/// Autogenerated definition for constant ${constant}\n`;
+        const comment =
+          `/// Autogenerated definition for constant ${constant}\n` + renderSource(documentation[constant]);
         return `${comment}#[allow(dead_code)]\npub const ${constantCase(constant)}: i32 = ${value};\n`;
       })
       .join("") + "\n";
 
+  const typePrefixes: Record<string, string> = {};
+  Object.keys(types).forEach((typeName) => {
+    const feature = typeFeatures[typeName];
+    typePrefixes[typeName] = feature === null ? "" : `#[cfg(feature = "${feature}")]\n`;
+  });
+
   Object.keys(types).forEach((typeName) => {
     const typeDefinition = types[typeName];
 
-    const comment = `/// Autogenerated definition for type ${typeName}\n`;
-    const typePrefix = `${mainTypes.has(typeName) ? "" : '#[cfg(feature = "all-types")]\n'}`;
-    if (typeDefinition.type !== "enum" && typeDefinition.type !== "struct" && typeDefinition.type !== "union") {
+    const comment = `/// Autogenerated definition for type ${typeName}\n` + renderSource(documentation[typeName]);
+    const typePrefix = typePrefixes[typeName];
+    if (
+      typeDefinition.type !== "enum" &&
+      typeDefinition.type !== "struct" &&
+      typeDefinition.type !== "union" &&
+      typeDefinition.type !== "newtype"
+    ) {
       result += `${comment}#[allow(dead_code)]\n${typePrefix}pub type ${typeName} = ${determineTypeReference(
         typeDefinition
       )};\n\n`;
@@ -70,23 +134,52 @@ export function generateXdrDefinition(
         typeDefinition.type === "enum" ? "Debug, Copy, Clone, Eq, PartialEq" : "Debug, Clone, Eq, PartialEq";
       result += `${comment}#[allow(dead_code)]\n${typePrefix}#[derive(${derive})]\n${typeDefinition.typeDefinition}\n\n`;
       result += `${typePrefix}impl XdrCodec for ${typeName} {${typeDefinition.typeImplementation}\n}\n\n`;
+      typeDefinition.additionalImplementations.forEach((implementation) => {
+        result += `${typePrefix}${implementation}\n\n`;
+      });
+      result += `${typePrefix}${generateXdrTypeImplementation(typeName, typeDefinition)}\n\n`;
     }
   });
 
+  result += generateMetadataLookup(types, typePrefixes);
+
+  result += generateSchemaLookup(types);
+
+  // the module `dynamic` uses the schema of the module `xdr`, which re-exports the first of several versions
+  const version = process.env.VERSION_MODULE;
+  const dynamicRoundTrip = version === undefined || version === process.env.DEFAULT_VERSION_MODULE;
+  result += generateTestModule(types, typePrefixes, dynamicRoundTrip);
+
   const mainFileName = process.env.MAIN_FILE_NAME;
   if (!mainFileName) {
     throw new Error('Environment variable "MAIN_FILE_NAME" not specified');
   }
 
+  mkdirSync(dirname(join(outputPath, mainFileName)), { recursive: true });
   writeFileSync(join(outputPath, mainFileName), result);
 }
 
+// Replace the placeholder feature `all-types` in the copied `Cargo.toml` by one feature per `.x` file
+export function writeCargoFeatures(outputPath: string, featureDependencies: Record<string, string[]>) {
+  const manifestPath = join(outputPath, "Cargo.toml");
+  const manifest = readFileSync(manifestPath, "utf8");
+  if (!/^all-types = \[\]$/m.test(manifest)) {
+    throw new Error(`The file "${manifestPath}" does not define the feature "all-types"`);
+  }
+
+  writeFileSync(manifestPath, manifest.replace(/^all-types = \[\]\n/m, renderCargoFeatures(featureDependencies)));
+}
+
 const staticFiles = [
   "src/xdr_codec.rs",
   "src/streams.rs",
   "src/lib.rs",
   "src/compound_types.rs",
-  "Cargo.lock",
+  "src/enums.rs",
+  "src/type_metadata.rs",
+  "src/dynamic.rs",
+  "src/txrep.rs",
+  "src/bounded_vec.rs",
   "Cargo.toml",
   "README.md",
 ];
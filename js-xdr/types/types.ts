@@ -76,25 +76,72 @@ export interface ReferenceType {
   name: string;
 }
 
+export interface BoxType {
+  type: "box";
+  innerType: ReferableXdrType;
+}
+
+export interface EnumCase {
+  name: string;
+  xdrName: string;
+  identifier: string;
+  value: number;
+}
+
 export interface EnumType {
   type: "enum";
   typeDefinition: string;
   typeImplementation: string;
+  additionalImplementations: string[];
   noOfCases: number;
+  cases: EnumCase[];
+  // an open enum has the additional case `Unknown(i32)` for values that are not defined
+  open: boolean;
+}
+
+export interface StructField {
+  name: string;
+  identifier: string;
+  fieldType: ReferableXdrType;
 }
 
 export interface StructType {
   type: "struct";
   typeDefinition: string;
   typeImplementation: string;
+  additionalImplementations: string[];
   referredTypes: Record<string, true>;
+  fields: StructField[];
+}
+
+export interface UnionArm {
+  switchValue: number | string;
+  caseIdentifier: string;
+  caseName: string;
+  discriminant: number;
+  armName?: string;
+  armType?: ReferableXdrType;
 }
 
 export interface UnionType {
   type: "union";
   typeDefinition: string;
   typeImplementation: string;
+  additionalImplementations: string[];
+  referredTypes: Record<string, true>;
+  switchOn: IntType | UIntType | BoolType | ReferenceType;
+  switchName: string;
+  arms: UnionArm[];
+  hasDefaultArm: boolean;
+}
+
+export interface NewtypeType {
+  type: "newtype";
+  typeDefinition: string;
+  typeImplementation: string;
+  additionalImplementations: string[];
   referredTypes: Record<string, true>;
+  innerType: ReferableXdrType;
 }
 
 export type ReferableXdrType =
@@ -113,13 +160,14 @@ export type ReferableXdrType =
   | VoidType
   | BoolType
   | OptionType
-  | ReferenceType;
+  | ReferenceType
+  | BoxType;
 
-export type ComplexXdrType = EnumType | StructType | UnionType;
+export type ComplexXdrType = EnumType | StructType | UnionType | NewtypeType;
 
 export type XdrType = ReferableXdrType | ComplexXdrType;
 
-function lengthToString(length: number | ReferenceType): string {
+export function lengthToString(length: number | ReferenceType): string {
   if (typeof length === "number") return String(length);
   return length.name;
 }
@@ -158,6 +206,8 @@ export function determineTypeReference(type: ReferableXdrType): string {
       return `Option<${determineTypeReference(type.innerType)}>`;
     case "reference":
       return type.name;
+    case "box":
+      return `Box<${determineTypeReference(type.innerType)}>`;
   }
 }
 
@@ -197,6 +247,8 @@ export function determineFullyQualifiedTypeReference(type: ReferableXdrType): st
       return `Option::<${determineFullyQualifiedTypeReference(type.innerType)}>`;
     case "reference":
       return type.name;
+    case "box":
+      return `Box::<${determineFullyQualifiedTypeReference(type.innerType)}>`;
   }
 }
 
@@ -219,11 +271,13 @@ export function determineDependencies(type: XdrType): Record<string, true> {
     case "unlimitedVarArray":
     case "array":
     case "option":
+    case "box":
       return determineDependencies(type.innerType);
     case "reference":
       return { [type.name]: true };
     case "struct":
     case "union":
+    case "newtype":
       return type.referredTypes;
   }
 }
@@ -0,0 +1,58 @@
+//! Conversions between the compound types and Substrate's `BoundedVec`
+//!
+//! Pallets usually store variable length data as `BoundedVec<T, S>`, where the bound is given by
+//! a `S: Get<u32>`, e.g., `ConstU32<N>`. The type is the one of the crate `bounded-collections`,
+//! which `sp_runtime` re-exports, so no `std` feature of the runtime is needed. The conversions
+//! in this module check the bound of the target type, so that values can move into and out of
+//! storage without manual length checks.
+
+use bounded_collections::{BoundedVec, Get};
+use core::convert::TryFrom;
+
+use crate::compound_types::{ExceedsMaximumLengthError, LimitedVarArray, LimitedVarOpaque};
+
+fn bound_as_i32<S: Get<u32>>() -> i32 {
+    S::get().min(i32::MAX as u32) as i32
+}
+
+fn to_bounded_vec<T, S: Get<u32>>(
+    vec: sp_std::vec::Vec<T>,
+) -> Result<BoundedVec<T, S>, ExceedsMaximumLengthError> {
+    let requested_length = vec.len();
+    BoundedVec::try_from(vec).map_err(|_| ExceedsMaximumLengthError {
+        requested_length,
+        allowed_length: bound_as_i32::<S>(),
+    })
+}
+
+impl<S: Get<u32>, const N: i32> TryFrom<LimitedVarOpaque<N>> for BoundedVec<u8, S> {
+    type Error = ExceedsMaximumLengthError;
+
+    fn try_from(value: LimitedVarOpaque<N>) -> Result<Self, Self::Error> {
+        to_bounded_vec(value.into_vec())
+    }
+}
+
+impl<S: Get<u32>, const N: i32> TryFrom<BoundedVec<u8, S>> for LimitedVarOpaque<N> {
+    type Error = ExceedsMaximumLengthError;
+
+    fn try_from(value: BoundedVec<u8, S>) -> Result<Self, Self::Error> {
+        LimitedVarOpaque::new(value.into_inner())
+    }
+}
+
+impl<T, S: Get<u32>, const N: i32> TryFrom<LimitedVarArray<T, N>> for BoundedVec<T, S> {
+    type Error = ExceedsMaximumLengthError;
+
+    fn try_from(value: LimitedVarArray<T, N>) -> Result<Self, Self::Error> {
+        to_bounded_vec(value.into_vec())
+    }
+}
+
+impl<T, S: Get<u32>, const N: i32> TryFrom<BoundedVec<T, S>> for LimitedVarArray<T, N> {
+    type Error = ExceedsMaximumLengthError;
+
+    fn try_from(value: BoundedVec<T, S>) -> Result<Self, Self::Error> {
+        LimitedVarArray::new(value.into_inner())
+    }
+}
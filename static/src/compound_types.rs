@@ -1,6 +1,9 @@
 //! Generic types for encoding XDR variable length arrays and strings
 
-use core::convert::AsRef;
+use core::convert::{AsRef, TryFrom};
+use core::ops::{Deref, DerefMut, Index, IndexMut};
+use core::slice::{self, SliceIndex};
+use core::{fmt, str};
 use sp_std::{prelude::*, vec::Vec};
 
 use crate::streams::{DecodeError, ReadStream, WriteStream};
@@ -13,6 +16,151 @@ pub struct ExceedsMaximumLengthError {
     pub allowed_length: i32,
 }
 
+/// Error to indicate that a limited string is not valid text
+#[derive(Debug, Clone)]
+pub enum InvalidTextError {
+    ExceedsMaximumLength(ExceedsMaximumLengthError),
+    /// The byte at the given position is not allowed
+    InvalidByte {
+        position: usize,
+    },
+}
+
+impl From<ExceedsMaximumLengthError> for InvalidTextError {
+    fn from(error: ExceedsMaximumLengthError) -> Self {
+        InvalidTextError::ExceedsMaximumLength(error)
+    }
+}
+
+/// Construction of a bounded type from an array whose length is checked at compile time
+///
+/// The types in this module store their elements in a `Vec`, so they cannot be constructed
+/// by a `const fn`. Instead, `from_array` fails to compile if the length `M` of the array
+/// exceeds the maximal length of the type. See also the macros `limited_vec!` and
+/// `limited_string!`, and `LimitedLiteral` for `const` items.
+///
+/// The length is checked when `from_array` is monomorphized, so `cargo check` accepts an
+/// array that is too long. Only `cargo build` fails, with error E0080 pointing at the
+/// assertion in this module:
+///
+/// ```compile_fail,E0080
+/// use substrate_stellar_xdr::{compound_types::LimitedString, limited_string};
+///
+/// let home_domain: LimitedString<4> = limited_string!("stellar.org");
+/// ```
+pub trait FromArray<T, const M: usize> {
+    /// Construct a value from an array, checking the maximal length at compile time
+    fn from_array(array: [T; M]) -> Self;
+}
+
+/// Compile-time assertion that the length `M` does not exceed the maximal length `N`
+struct LengthWithinBound<const M: usize, const N: i32>;
+
+impl<const M: usize, const N: i32> LengthWithinBound<M, N> {
+    const ASSERTION: () = assert!(M <= N as usize, "the array exceeds the maximal length");
+}
+
+/// A bounded literal that can be constructed in a `const` item
+///
+/// A `LimitedLiteral<T, N>` refers to at most `N` elements of static data and converts into a
+/// `LimitedVarArray<T, N>` or, for bytes, into a `LimitedVarOpaque<N>` or `LimitedString<N>`.
+/// `new` is a `const fn` that asserts the maximal length, so a literal that is too long fails
+/// to compile when the `const` item is evaluated, also with `cargo check`:
+///
+/// ```
+/// use substrate_stellar_xdr::compound_types::{LimitedLiteral, LimitedString, LimitedVarArray};
+///
+/// const HOME_DOMAIN: LimitedLiteral<u8, 32> = LimitedLiteral::new(b"stellar.org");
+/// const THRESHOLDS: LimitedLiteral<u32, 4> = LimitedLiteral::new(&[1, 2, 3]);
+///
+/// let home_domain = LimitedString::from(HOME_DOMAIN);
+/// assert_eq!(home_domain.get_vec(), b"stellar.org");
+/// let thresholds = LimitedVarArray::from(THRESHOLDS);
+/// assert_eq!(thresholds.get_vec(), &[1, 2, 3]);
+/// ```
+///
+/// ```compile_fail,E0080
+/// use substrate_stellar_xdr::compound_types::LimitedLiteral;
+///
+/// const MEMO_TEXT: LimitedLiteral<u8, 4> = LimitedLiteral::new(b"stellar.org");
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LimitedLiteral<T: 'static, const N: i32>(&'static [T]);
+
+impl<T, const N: i32> LimitedLiteral<T, N> {
+    /// Construct a new `LimitedLiteral`, panics if there are more than `N` elements
+    ///
+    /// In a `const` item, the panic is a compile error.
+    pub const fn new(elements: &'static [T]) -> Self {
+        assert!(
+            elements.len() <= N as usize,
+            "the literal exceeds the maximal length"
+        );
+        LimitedLiteral(elements)
+    }
+
+    /// Returns the elements of the literal
+    pub const fn as_slice(&self) -> &'static [T] {
+        self.0
+    }
+}
+
+/// Convert a string into a byte array of its length, used by `limited_string!`
+#[doc(hidden)]
+pub const fn text_to_array<const M: usize>(text: &str) -> [u8; M] {
+    let bytes = text.as_bytes();
+    let mut array = [0; M];
+    let mut index = 0;
+    while index < M {
+        array[index] = bytes[index];
+        index += 1;
+    }
+    array
+}
+
+/// Construct a `LimitedVarArray` or `LimitedVarOpaque` from a list of elements
+///
+/// The maximal length is checked at compile time, see `compound_types::FromArray`.
+///
+/// ```
+/// use substrate_stellar_xdr::{
+///     compound_types::{LimitedVarArray, LimitedVarOpaque},
+///     limited_vec,
+/// };
+///
+/// let data: LimitedVarOpaque<64> = limited_vec![0xca, 0xfe];
+/// assert_eq!(data.get_vec(), &[0xca, 0xfe]);
+/// let weights: LimitedVarArray<u32, 20> = limited_vec![1, 2, 3];
+/// assert_eq!(weights.len(), 3);
+/// ```
+#[macro_export]
+macro_rules! limited_vec {
+    ($($element:expr),* $(,)?) => {
+        $crate::compound_types::FromArray::from_array([$($element),*])
+    };
+}
+
+/// Construct a `LimitedString` from a string literal
+///
+/// The maximal length is checked at compile time, see `compound_types::FromArray`.
+///
+/// ```
+/// use substrate_stellar_xdr::{compound_types::LimitedString, limited_string};
+///
+/// let home_domain: LimitedString<32> = limited_string!("stellar.org");
+/// assert_eq!(home_domain.get_vec(), b"stellar.org");
+/// ```
+#[macro_export]
+macro_rules! limited_string {
+    ($text:expr) => {{
+        const TEXT: &str = $text;
+        const LENGTH: usize = TEXT.len();
+        $crate::compound_types::FromArray::from_array(
+            $crate::compound_types::text_to_array::<LENGTH>(TEXT),
+        )
+    }};
+}
+
 /// Type for binary data whose length is not predefined but bounded by a constant
 ///
 /// The const generic `N` specifies the maxmimum number of bytes a value of this
@@ -40,6 +188,24 @@ impl<const N: i32> LimitedVarOpaque<N> {
     pub fn get_vec(&self) -> &Vec<u8> {
         &self.0
     }
+
+    /// Returns the underlying byte vector
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl<const N: i32, const M: usize> FromArray<u8, M> for LimitedVarOpaque<N> {
+    fn from_array(array: [u8; M]) -> Self {
+        let () = LengthWithinBound::<M, N>::ASSERTION;
+        LimitedVarOpaque(Vec::from(array))
+    }
+}
+
+impl<const N: i32> From<LimitedLiteral<u8, N>> for LimitedVarOpaque<N> {
+    fn from(literal: LimitedLiteral<u8, N>) -> Self {
+        LimitedVarOpaque(literal.0.to_vec())
+    }
 }
 
 impl<const N: i32> XdrCodec for LimitedVarOpaque<N> {
@@ -98,10 +264,69 @@ impl<const N: i32> LimitedString<N> {
         }
     }
 
+    /// Construct a new `LimitedString` from a byte vector that only contains ASCII characters
+    ///
+    /// This returns an error if the byte vector contains a byte that is not ASCII or if
+    /// its length exceeds `N`.
+    pub fn new_ascii(vec: Vec<u8>) -> Result<Self, InvalidTextError> {
+        if let Some(position) = vec.iter().position(|byte| !byte.is_ascii()) {
+            return Err(InvalidTextError::InvalidByte { position });
+        }
+        Ok(Self::new(vec)?)
+    }
+
     /// Returns a reference to the raw byte vector
     pub fn get_vec(&self) -> &Vec<u8> {
         &self.0
     }
+
+    /// Returns the string as `&str` if it is valid UTF-8
+    pub fn as_str(&self) -> Result<&str, str::Utf8Error> {
+        str::from_utf8(&self.0)
+    }
+}
+
+impl<const N: i32> TryFrom<&str> for LimitedString<N> {
+    type Error = ExceedsMaximumLengthError;
+
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        Self::new(text.as_bytes().to_vec())
+    }
+}
+
+impl<const N: i32, const M: usize> FromArray<u8, M> for LimitedString<N> {
+    fn from_array(array: [u8; M]) -> Self {
+        let () = LengthWithinBound::<M, N>::ASSERTION;
+        LimitedString(Vec::from(array))
+    }
+}
+
+impl<const N: i32> From<LimitedLiteral<u8, N>> for LimitedString<N> {
+    fn from(literal: LimitedLiteral<u8, N>) -> Self {
+        LimitedString(literal.0.to_vec())
+    }
+}
+
+/// Displays the string as text, invalid UTF-8 sequences are displayed as `\xNN` escapes
+impl<const N: i32> fmt::Display for LimitedString<N> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = &self.0[..];
+        loop {
+            match str::from_utf8(bytes) {
+                Ok(text) => return formatter.write_str(text),
+                Err(error) => {
+                    let (valid, invalid) = bytes.split_at(error.valid_up_to());
+                    // the prefix is valid UTF-8 by definition of `valid_up_to`
+                    formatter.write_str(str::from_utf8(valid).unwrap())?;
+                    let invalid_length = error.error_len().unwrap_or(invalid.len());
+                    for byte in &invalid[..invalid_length] {
+                        write!(formatter, "\\x{:02x}", byte)?;
+                    }
+                    bytes = &invalid[invalid_length..];
+                }
+            }
+        }
+    }
 }
 
 impl<const N: i32> XdrCodec for LimitedString<N> {
@@ -122,9 +347,9 @@ impl<const N: i32> XdrCodec for LimitedString<N> {
                 max_length: N,
                 actual_length: length,
             }),
-            false => Ok(
-                LimitedString::new(read_stream.read_next_binary_data(length as usize)?).unwrap(),
-            ),
+            false => {
+                Ok(LimitedString::new(read_stream.read_next_string(length as usize)?).unwrap())
+            }
         }
     }
 }
@@ -165,20 +390,169 @@ impl<T, const N: i32> LimitedVarArray<T, N> {
         &self.0
     }
 
-    /// Add an element to the byte vector
-    ///
-    /// Return an `Err` if the array already has the maximal number of elements.
-    pub fn push(&mut self, item: T) -> Result<(), ExceedsMaximumLengthError> {
-        if self.0.len() >= N as usize - 1 {
+    /// Returns the underlying vector
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+
+    /// Returns the number of elements that can still be added to the array
+    pub fn capacity_left(&self) -> usize {
+        N as usize - self.0.len()
+    }
+
+    fn ensure_capacity_left(&self, no_of_elements: usize) -> Result<(), ExceedsMaximumLengthError> {
+        if no_of_elements > self.capacity_left() {
             return Err(ExceedsMaximumLengthError {
-                requested_length: self.0.len() + 1,
+                requested_length: self.0.len() + no_of_elements,
                 allowed_length: N,
             });
         }
+        Ok(())
+    }
 
+    /// Add an element to the byte vector
+    ///
+    /// Return an `Err` if the array already has the maximal number of elements.
+    pub fn push(&mut self, item: T) -> Result<(), ExceedsMaximumLengthError> {
+        self.ensure_capacity_left(1)?;
         self.0.push(item);
         Ok(())
     }
+
+    /// Remove the last element and return it, or `None` if the array is empty
+    pub fn pop(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+
+    /// Insert an element at position `index`, shifting all elements after it to the right
+    ///
+    /// Return an `Err` if the array already has the maximal number of elements.
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, item: T) -> Result<(), ExceedsMaximumLengthError> {
+        self.ensure_capacity_left(1)?;
+        self.0.insert(index, item);
+        Ok(())
+    }
+
+    /// Remove and return the element at position `index`, shifting all elements after it to the
+    /// left
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        self.0.remove(index)
+    }
+
+    /// Retain only the elements specified by the predicate
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, predicate: F) {
+        self.0.retain(predicate)
+    }
+
+    /// Shorten the array to `length` elements, this has no effect if the array is shorter
+    pub fn truncate(&mut self, length: usize) {
+        self.0.truncate(length)
+    }
+
+    /// Append all elements of an iterator
+    ///
+    /// Return an `Err` and leave the array unchanged if the array would exceed the maximal
+    /// number of elements. The iterator is consumed only up to the first element that does
+    /// not fit, so the requested length of the error is one more than the maximal length.
+    pub fn extend<I: IntoIterator<Item = T>>(
+        &mut self,
+        items: I,
+    ) -> Result<(), ExceedsMaximumLengthError> {
+        // take one element more than fits, so that an overlong iterator is not consumed entirely
+        let mut items: Vec<T> = items.into_iter().take(self.capacity_left() + 1).collect();
+        self.ensure_capacity_left(items.len())?;
+        self.0.append(&mut items);
+        Ok(())
+    }
+
+    /// Convert every element, the result has the same length and thus the same bound
+    ///
+    /// Used by the conversions between protocol versions, which are only generated for several
+    /// versions.
+    #[allow(dead_code)]
+    pub(crate) fn map_elements<U, F: FnMut(T) -> U>(self, convert: F) -> LimitedVarArray<U, N> {
+        LimitedVarArray(self.0.into_iter().map(convert).collect())
+    }
+}
+
+impl<T, const N: i32> TryFrom<Vec<T>> for LimitedVarArray<T, N> {
+    type Error = ExceedsMaximumLengthError;
+
+    fn try_from(vec: Vec<T>) -> Result<Self, Self::Error> {
+        Self::new(vec)
+    }
+}
+
+impl<T, const N: i32, const M: usize> FromArray<T, M> for LimitedVarArray<T, N> {
+    fn from_array(array: [T; M]) -> Self {
+        let () = LengthWithinBound::<M, N>::ASSERTION;
+        LimitedVarArray(Vec::from(array))
+    }
+}
+
+impl<T: Clone, const N: i32> From<LimitedLiteral<T, N>> for LimitedVarArray<T, N> {
+    fn from(literal: LimitedLiteral<T, N>) -> Self {
+        LimitedVarArray(literal.0.to_vec())
+    }
+}
+
+/// The elements can be accessed and modified as a slice, which cannot change the length
+impl<T, const N: i32> Deref for LimitedVarArray<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T, const N: i32> DerefMut for LimitedVarArray<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.0
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, const N: i32> Index<I> for LimitedVarArray<T, N> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &I::Output {
+        &self.0[index]
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, const N: i32> IndexMut<I> for LimitedVarArray<T, N> {
+    fn index_mut(&mut self, index: I) -> &mut I::Output {
+        &mut self.0[index]
+    }
+}
+
+impl<T, const N: i32> IntoIterator for LimitedVarArray<T, N> {
+    type Item = T;
+    type IntoIter = sp_std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T, const N: i32> IntoIterator for &'a LimitedVarArray<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a, T, const N: i32> IntoIterator for &'a mut LimitedVarArray<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
 }
 
 impl<T: XdrCodec, const N: i32> XdrCodec for LimitedVarArray<T, N> {
@@ -0,0 +1,582 @@
+//! Decoding and encoding of XDR types that are only known at runtime
+//!
+//! The module `xdr` contains a compact schema of all XDR types (see `xdr::schema_definition`).
+//! The functions `decode_dynamic` and `encode_dynamic` use this schema to decode XDR of a type
+//! given by its name into a generic value tree (`XdrValue`) and to encode such a tree again.
+//! The function `guess_types` finds the types that XDR data of an unknown type can be decoded as.
+
+use core::convert::AsRef;
+use sp_std::{boxed::Box, vec::Vec};
+
+use crate::streams::{DecodeError, ReadStream, WriteStream};
+use crate::type_metadata::EnumCaseMetadata;
+use crate::xdr::{schema_definition, SCHEMA_TYPE_NAMES};
+
+/// The type of a value in the schema of the XDR types
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SchemaType {
+    Int,
+    UnsignedInt,
+    Hyper,
+    UnsignedHyper,
+    Bool,
+    /// Binary data of the given fixed length
+    Opaque(i32),
+    /// Binary data of variable length, bounded by the given maximal length
+    VarOpaque(i32),
+    /// A string, bounded by the given maximal length
+    String(i32),
+    /// An array of the given fixed length
+    Array(&'static SchemaType, i32),
+    /// An array of variable length, bounded by the given maximal length
+    VarArray(&'static SchemaType, i32),
+    /// An optional value
+    Optional(&'static SchemaType),
+    /// A type defined in the schema, given by its name
+    Reference(&'static str),
+}
+
+/// An arm of a union in the schema of the XDR types
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SchemaUnionArm {
+    /// The name of the discriminant case as in the XDR definition, e.g., `MEMO_TEXT`
+    pub case_name: &'static str,
+    /// The value of the discriminant
+    pub discriminant: i64,
+    /// The name and the type of the arm; `None` if the arm is void
+    pub arm: Option<(&'static str, SchemaType)>,
+}
+
+/// The definition of a named type in the schema of the XDR types
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SchemaDefinition {
+    /// An XDR typedef, i.e., another name for a type
+    Typedef(SchemaType),
+    /// An XDR enum and its cases
+    ///
+    /// If `open` is true, then values that are not covered by `cases` are valid as well.
+    Enum {
+        cases: &'static [EnumCaseMetadata],
+        open: bool,
+    },
+    /// An XDR struct and the names and types of its fields
+    Struct(&'static [(&'static str, SchemaType)]),
+    /// An XDR union
+    ///
+    /// If `has_default_arm` is true, then every discriminant that is not covered by `arms` is
+    /// valid and has a void arm.
+    Union {
+        /// The name of the discriminant as in the XDR definition, e.g., `type`
+        switch_name: &'static str,
+        switch: SchemaType,
+        arms: &'static [SchemaUnionArm],
+        has_default_arm: bool,
+    },
+}
+
+/// A generic XDR value
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum XdrValue {
+    Int(i32),
+    UnsignedInt(u32),
+    Hyper(i64),
+    UnsignedHyper(u64),
+    Bool(bool),
+    /// Binary data of fixed or variable length
+    Opaque(Vec<u8>),
+    /// A string, XDR strings are not necessarily valid UTF-8
+    String(Vec<u8>),
+    /// An array of fixed or variable length
+    Array(Vec<XdrValue>),
+    Optional(Option<Box<XdrValue>>),
+    /// A case of an enum, given by its name as in the XDR definition and its value
+    ///
+    /// Values of open enums that are not defined have the name `UNKNOWN_ENUM_CASE`.
+    Enum {
+        name: &'static str,
+        value: i32,
+    },
+    /// A struct, given by the names and values of its fields in the order of the XDR definition
+    Struct(Vec<(&'static str, XdrValue)>),
+    /// A union, given by its discriminant and the name and value of its arm (`None` for void arms)
+    Union {
+        discriminant: Box<XdrValue>,
+        arm: Option<(&'static str, Box<XdrValue>)>,
+    },
+}
+
+/// The name of values of open enums that are not defined, see `SchemaDefinition::Enum`
+pub const UNKNOWN_ENUM_CASE: &str = "UNKNOWN";
+
+/// Returns the enum value for the integer `value`
+///
+/// This is `None` if the enum is not open and `value` is not one of its `cases`.
+pub fn enum_value(cases: &[EnumCaseMetadata], open: bool, value: i32) -> Option<XdrValue> {
+    match cases.iter().find(|case| case.value == value) {
+        Some(case) => Some(XdrValue::Enum {
+            name: case.name,
+            value,
+        }),
+        None if open => Some(XdrValue::Enum {
+            name: UNKNOWN_ENUM_CASE,
+            value,
+        }),
+        None => None,
+    }
+}
+
+/// An error type for decoding and encoding `XdrValue`s
+#[derive(Debug)]
+pub enum DynamicError {
+    /// The schema does not contain a type of the given name
+    UnknownType,
+    /// The XDR data cannot be decoded
+    Decode(DecodeError),
+    /// The value does not conform to the schema of the type
+    InvalidValue,
+}
+
+impl From<DecodeError> for DynamicError {
+    fn from(error: DecodeError) -> Self {
+        DynamicError::Decode(error)
+    }
+}
+
+pub(crate) fn lookup(type_name: &str) -> Result<&'static SchemaDefinition, DynamicError> {
+    schema_definition(type_name).ok_or(DynamicError::UnknownType)
+}
+
+/// Returns the integer value of the discriminant of a union, if the value is a valid discriminant
+pub fn discriminant_value(value: &XdrValue) -> Option<i64> {
+    match value {
+        XdrValue::Int(value) => Some(*value as i64),
+        XdrValue::UnsignedInt(value) => Some(*value as i64),
+        XdrValue::Bool(value) => Some(*value as i64),
+        XdrValue::Enum { value, .. } => Some(*value as i64),
+        _ => None,
+    }
+}
+
+fn decode_length<T: AsRef<[u8]>>(
+    read_stream: &mut ReadStream<T>,
+    max_length: i32,
+    generate_error: fn(usize, i32, i32) -> DecodeError,
+) -> Result<usize, DecodeError> {
+    let length = read_stream.read_next_u32()? as i32;
+    if length > max_length || length < 0 {
+        return Err(generate_error(
+            read_stream.get_position(),
+            max_length,
+            length,
+        ));
+    }
+    Ok(length as usize)
+}
+
+/// The maximal number of nested schema definitions that the decoder descends into
+///
+/// Some types are recursive, e.g., `ClaimPredicate`, so that every few bytes of crafted input could
+/// otherwise add a stack frame until the stack overflows. The Stellar types nest far less deeply.
+pub const MAX_DEPTH: usize = 100;
+
+/// Decode a value of the given schema type from a read stream
+pub fn decode_value<T: AsRef<[u8]>>(
+    schema_type: &SchemaType,
+    read_stream: &mut ReadStream<T>,
+) -> Result<XdrValue, DynamicError> {
+    decode_value_at_depth(schema_type, read_stream, 0)
+}
+
+fn decode_value_at_depth<T: AsRef<[u8]>>(
+    schema_type: &SchemaType,
+    read_stream: &mut ReadStream<T>,
+    depth: usize,
+) -> Result<XdrValue, DynamicError> {
+    Ok(match schema_type {
+        SchemaType::Int => XdrValue::Int(read_stream.read_next_i32()?),
+        SchemaType::UnsignedInt => XdrValue::UnsignedInt(read_stream.read_next_u32()?),
+        SchemaType::Hyper => XdrValue::Hyper(read_stream.read_next_i64()?),
+        SchemaType::UnsignedHyper => XdrValue::UnsignedHyper(read_stream.read_next_u64()?),
+        SchemaType::Bool => match read_stream.read_next_i32()? {
+            0 => XdrValue::Bool(false),
+            1 => XdrValue::Bool(true),
+            found_integer => {
+                return Err(DecodeError::InvalidBoolean {
+                    found_integer,
+                    at_position: read_stream.get_position(),
+                }
+                .into())
+            }
+        },
+        SchemaType::Opaque(length) => {
+            XdrValue::Opaque(read_stream.read_next_binary_data(*length as usize)?)
+        }
+        SchemaType::VarOpaque(max_length) => {
+            let length = decode_length(
+                read_stream,
+                *max_length,
+                |at_position, max_length, actual_length| DecodeError::VarOpaqueExceedsMaxLength {
+                    at_position,
+                    max_length,
+                    actual_length,
+                },
+            )?;
+            XdrValue::Opaque(read_stream.read_next_binary_data(length)?)
+        }
+        SchemaType::String(max_length) => {
+            let length = decode_length(
+                read_stream,
+                *max_length,
+                |at_position, max_length, actual_length| DecodeError::StringExceedsMaxLength {
+                    at_position,
+                    max_length,
+                    actual_length,
+                },
+            )?;
+            XdrValue::String(read_stream.read_next_string(length)?)
+        }
+        SchemaType::Array(inner_type, length) => {
+            let mut result = Vec::with_capacity(*length as usize);
+            for _ in 0..*length {
+                result.push(decode_value_at_depth(inner_type, read_stream, depth)?);
+            }
+            XdrValue::Array(result)
+        }
+        SchemaType::VarArray(inner_type, max_length) => {
+            let length = decode_length(
+                read_stream,
+                *max_length,
+                |at_position, max_length, actual_length| DecodeError::VarArrayExceedsMaxLength {
+                    at_position,
+                    max_length,
+                    actual_length,
+                },
+            )?;
+            let mut result = Vec::new();
+            for _ in 0..length {
+                result.push(decode_value_at_depth(inner_type, read_stream, depth)?);
+            }
+            XdrValue::Array(result)
+        }
+        SchemaType::Optional(inner_type) => match read_stream.read_next_u32()? {
+            0 => XdrValue::Optional(None),
+            1 => XdrValue::Optional(Some(Box::new(decode_value_at_depth(
+                inner_type,
+                read_stream,
+                depth,
+            )?))),
+            has_code => {
+                return Err(DecodeError::InvalidOptional {
+                    at_position: read_stream.get_position(),
+                    has_code,
+                }
+                .into())
+            }
+        },
+        SchemaType::Reference(type_name) => {
+            decode_definition_at_depth(lookup(type_name)?, read_stream, depth + 1)?
+        }
+    })
+}
+
+/// Decode a value of the given schema definition from a read stream
+///
+/// Fails with `DecodeError::MaximumDepthExceeded` if the value nests more than `MAX_DEPTH`
+/// definitions.
+pub fn decode_definition<T: AsRef<[u8]>>(
+    definition: &SchemaDefinition,
+    read_stream: &mut ReadStream<T>,
+) -> Result<XdrValue, DynamicError> {
+    decode_definition_at_depth(definition, read_stream, 0)
+}
+
+fn decode_definition_at_depth<T: AsRef<[u8]>>(
+    definition: &SchemaDefinition,
+    read_stream: &mut ReadStream<T>,
+    depth: usize,
+) -> Result<XdrValue, DynamicError> {
+    if depth > MAX_DEPTH {
+        return Err(DecodeError::MaximumDepthExceeded {
+            at_position: read_stream.get_position(),
+        }
+        .into());
+    }
+
+    match definition {
+        SchemaDefinition::Typedef(schema_type) => {
+            decode_value_at_depth(schema_type, read_stream, depth)
+        }
+        SchemaDefinition::Enum { cases, open } => {
+            let value = read_stream.read_next_i32()?;
+            match enum_value(cases, *open, value) {
+                Some(enum_value) => Ok(enum_value),
+                None => Err(DecodeError::InvalidEnumDiscriminator {
+                    at_position: read_stream.get_position(),
+                }
+                .into()),
+            }
+        }
+        SchemaDefinition::Struct(fields) => {
+            let mut result = Vec::with_capacity(fields.len());
+            for (name, schema_type) in fields.iter() {
+                result.push((
+                    *name,
+                    decode_value_at_depth(schema_type, read_stream, depth)?,
+                ));
+            }
+            Ok(XdrValue::Struct(result))
+        }
+        SchemaDefinition::Union {
+            switch,
+            arms,
+            has_default_arm,
+            ..
+        } => {
+            let discriminant = decode_value_at_depth(switch, read_stream, depth)?;
+            let discriminant_value =
+                discriminant_value(&discriminant).ok_or(DynamicError::InvalidValue)?;
+
+            let arm = match arms
+                .iter()
+                .find(|arm| arm.discriminant == discriminant_value)
+            {
+                Some(SchemaUnionArm {
+                    arm: Some((name, schema_type)),
+                    ..
+                }) => Some((
+                    *name,
+                    Box::new(decode_value_at_depth(schema_type, read_stream, depth)?),
+                )),
+                Some(SchemaUnionArm { arm: None, .. }) => None,
+                None if *has_default_arm => None,
+                None => {
+                    return Err(DecodeError::InvalidEnumDiscriminator {
+                        at_position: read_stream.get_position(),
+                    }
+                    .into())
+                }
+            };
+
+            Ok(XdrValue::Union {
+                discriminant: Box::new(discriminant),
+                arm,
+            })
+        }
+    }
+}
+
+fn encode_length(
+    length: usize,
+    max_length: i32,
+    write_stream: &mut WriteStream,
+) -> Result<(), DynamicError> {
+    if length > max_length as usize {
+        return Err(DynamicError::InvalidValue);
+    }
+    write_stream.write_next_u32(length as u32);
+    Ok(())
+}
+
+/// Encode a value of the given schema type to a write stream
+pub fn encode_value(
+    schema_type: &SchemaType,
+    value: &XdrValue,
+    write_stream: &mut WriteStream,
+) -> Result<(), DynamicError> {
+    match (schema_type, value) {
+        (SchemaType::Int, XdrValue::Int(value)) => write_stream.write_next_i32(*value),
+        (SchemaType::UnsignedInt, XdrValue::UnsignedInt(value)) => {
+            write_stream.write_next_u32(*value)
+        }
+        (SchemaType::Hyper, XdrValue::Hyper(value)) => write_stream.write_next_i64(*value),
+        (SchemaType::UnsignedHyper, XdrValue::UnsignedHyper(value)) => {
+            write_stream.write_next_u64(*value)
+        }
+        (SchemaType::Bool, XdrValue::Bool(value)) => write_stream.write_next_i32(*value as i32),
+        (SchemaType::Opaque(length), XdrValue::Opaque(value)) => {
+            if value.len() != *length as usize {
+                return Err(DynamicError::InvalidValue);
+            }
+            write_stream.write_next_binary_data(value);
+        }
+        (SchemaType::VarOpaque(max_length), XdrValue::Opaque(value))
+        | (SchemaType::String(max_length), XdrValue::String(value)) => {
+            encode_length(value.len(), *max_length, write_stream)?;
+            write_stream.write_next_binary_data(value);
+        }
+        (SchemaType::Array(inner_type, length), XdrValue::Array(values)) => {
+            if values.len() != *length as usize {
+                return Err(DynamicError::InvalidValue);
+            }
+            for value in values.iter() {
+                encode_value(inner_type, value, write_stream)?;
+            }
+        }
+        (SchemaType::VarArray(inner_type, max_length), XdrValue::Array(values)) => {
+            encode_length(values.len(), *max_length, write_stream)?;
+            for value in values.iter() {
+                encode_value(inner_type, value, write_stream)?;
+            }
+        }
+        (SchemaType::Optional(_), XdrValue::Optional(None)) => write_stream.write_next_u32(0),
+        (SchemaType::Optional(inner_type), XdrValue::Optional(Some(value))) => {
+            write_stream.write_next_u32(1);
+            encode_value(inner_type, value, write_stream)?;
+        }
+        (SchemaType::Reference(type_name), value) => {
+            encode_definition(lookup(type_name)?, value, write_stream)?
+        }
+        _ => return Err(DynamicError::InvalidValue),
+    }
+
+    Ok(())
+}
+
+/// Encode a value of the given schema definition to a write stream
+pub fn encode_definition(
+    definition: &SchemaDefinition,
+    value: &XdrValue,
+    write_stream: &mut WriteStream,
+) -> Result<(), DynamicError> {
+    match (definition, value) {
+        (SchemaDefinition::Typedef(schema_type), value) => {
+            encode_value(schema_type, value, write_stream)?
+        }
+        (SchemaDefinition::Enum { cases, open }, XdrValue::Enum { value, .. }) => {
+            if !open && !cases.iter().any(|case| case.value == *value) {
+                return Err(DynamicError::InvalidValue);
+            }
+            write_stream.write_next_i32(*value);
+        }
+        (SchemaDefinition::Struct(fields), XdrValue::Struct(values)) => {
+            if fields.len() != values.len() {
+                return Err(DynamicError::InvalidValue);
+            }
+            for ((name, schema_type), (value_name, value)) in fields.iter().zip(values.iter()) {
+                if name != value_name {
+                    return Err(DynamicError::InvalidValue);
+                }
+                encode_value(schema_type, value, write_stream)?;
+            }
+        }
+        (
+            SchemaDefinition::Union {
+                switch,
+                arms,
+                has_default_arm,
+                ..
+            },
+            XdrValue::Union { discriminant, arm },
+        ) => {
+            let discriminant_value =
+                discriminant_value(discriminant).ok_or(DynamicError::InvalidValue)?;
+            encode_value(switch, discriminant, write_stream)?;
+
+            match (
+                arms.iter()
+                    .find(|arm| arm.discriminant == discriminant_value),
+                arm,
+            ) {
+                (
+                    Some(SchemaUnionArm {
+                        arm: Some((name, schema_type)),
+                        ..
+                    }),
+                    Some((value_name, value)),
+                ) if name == value_name => encode_value(schema_type, value, write_stream)?,
+                (Some(SchemaUnionArm { arm: None, .. }), None) => {}
+                (None, None) if *has_default_arm => {}
+                _ => return Err(DynamicError::InvalidValue),
+            }
+        }
+        _ => return Err(DynamicError::InvalidValue),
+    }
+
+    Ok(())
+}
+
+/// Decode binary XDR of the type with the given name into a generic value tree
+pub fn decode_dynamic<T: AsRef<[u8]>>(type_name: &str, input: T) -> Result<XdrValue, DynamicError> {
+    let mut read_stream = ReadStream::new(input);
+    let value = decode_definition(lookup(type_name)?, &mut read_stream)?;
+    if read_stream.no_of_bytes_left_to_read() != 0 {
+        return Err(DecodeError::TypeEndsTooEarly {
+            remaining_no_of_bytes: read_stream.no_of_bytes_left_to_read(),
+        }
+        .into());
+    }
+
+    Ok(value)
+}
+
+/// Encode a generic value tree as binary XDR of the type with the given name
+pub fn encode_dynamic(type_name: &str, value: &XdrValue) -> Result<Vec<u8>, DynamicError> {
+    let mut write_stream = WriteStream::new();
+    encode_definition(lookup(type_name)?, value, &mut write_stream)?;
+    Ok(write_stream.get_result())
+}
+
+/// Root types that users commonly exchange, ranked first by `guess_types` in this order
+const WELL_KNOWN_TYPES: &[&str] = &[
+    "TransactionEnvelope",
+    "TransactionResult",
+    "TransactionMeta",
+    "TransactionResultPair",
+    "TransactionSignaturePayload",
+    "LedgerEntry",
+    "LedgerKey",
+    "LedgerEntryChanges",
+    "LedgerHeader",
+    "ScpEnvelope",
+    "StellarMessage",
+];
+
+/// A type that XDR data of an unknown type can be decoded as
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TypeGuess {
+    pub type_name: &'static str,
+    pub value: XdrValue,
+}
+
+/// Returns the number of nodes of a generic value tree
+fn value_size(value: &XdrValue) -> usize {
+    1 + match value {
+        XdrValue::Array(elements) => elements.iter().map(value_size).sum(),
+        XdrValue::Optional(Some(value)) => value_size(value),
+        XdrValue::Struct(fields) => fields.iter().map(|(_, value)| value_size(value)).sum(),
+        XdrValue::Union { discriminant, arm } => {
+            value_size(discriminant) + arm.as_ref().map_or(0, |(_, value)| value_size(value))
+        }
+        _ => 0,
+    }
+}
+
+/// Find all types that binary XDR of an unknown type can be decoded as
+///
+/// A type is a candidate if the data decodes as this type without trailing bytes and the decoded
+/// value encodes to exactly the same bytes again. The candidates are ranked by plausibility: the
+/// well-known root types such as `TransactionEnvelope` come first, followed by the remaining
+/// types with larger decoded value trees first.
+pub fn guess_types<T: AsRef<[u8]>>(input: T) -> Vec<TypeGuess> {
+    let input = input.as_ref();
+
+    let mut guesses: Vec<TypeGuess> = SCHEMA_TYPE_NAMES
+        .iter()
+        .filter_map(|type_name| {
+            let value = decode_dynamic(type_name, input).ok()?;
+            match encode_dynamic(type_name, &value) {
+                Ok(encoded) if encoded == input => Some(TypeGuess { type_name, value }),
+                _ => None,
+            }
+        })
+        .collect();
+
+    guesses.sort_by_cached_key(|guess| {
+        let rank = WELL_KNOWN_TYPES
+            .iter()
+            .position(|well_known| *well_known == guess.type_name)
+            .unwrap_or(WELL_KNOWN_TYPES.len());
+        (rank, usize::MAX - value_size(&guess.value), guess.type_name)
+    });
+    guesses
+}
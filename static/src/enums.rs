@@ -0,0 +1,29 @@
+//! Error types for the conversions of the XDR enums
+//!
+//! Every enum in the module `xdr` can be converted from its integer value (`TryFrom<i32>`) and
+//! from the name of its case as in the XDR definition (`FromStr`).
+
+use core::fmt;
+
+/// The integer is not the value of any case of the enum
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct InvalidEnumValue {
+    /// The integer that does not belong to a case
+    pub value: i32,
+}
+
+impl fmt::Display for InvalidEnumValue {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{} is not a valid enum value", self.value)
+    }
+}
+
+/// The string is not the name of any case of the enum
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct UnknownEnumName;
+
+impl fmt::Display for UnknownEnumName {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("unknown enum case name")
+    }
+}
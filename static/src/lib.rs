@@ -5,7 +5,17 @@
 
 #![no_std]
 
+extern crate alloc;
+
+#[cfg(feature = "bounded-vec")]
+pub mod bounded_vec;
 pub mod compound_types;
+#[cfg(feature = "dynamic")]
+pub mod dynamic;
+pub mod enums;
 pub mod streams;
+#[cfg(feature = "txrep")]
+pub mod txrep;
+pub mod type_metadata;
 pub mod xdr;
 pub mod xdr_codec;
@@ -1,7 +1,8 @@
 //! Streams for efficient encoding and decoding
 
-use core::convert::{AsRef, TryInto};
-use core::iter;
+use base64::{decode_config_slice, encode_config_slice, Config};
+use core::cmp::min;
+use core::convert::AsRef;
 
 use sp_std::vec::Vec;
 
@@ -69,25 +70,117 @@ pub enum DecodeError {
 
     /// The base64 encoding of the binary XDR is invalid
     InvalidBase64,
+
+    /// The hex encoding of the binary XDR is invalid
+    InvalidHex,
+
+    /// The XDR contains a string that is not valid text
+    ///
+    /// This is only checked if the `ReadStream` has a `TextValidation` other than `Unchecked`.
+    InvalidText { at_position: usize },
+
+    /// The XDR nests values deeper than the decoder allows
+    ///
+    /// This is only checked by the schema-driven decoder of the module `dynamic`, see
+    /// `dynamic::MAX_DEPTH`.
+    MaximumDepthExceeded { at_position: usize },
+}
+
+/// The validation of XDR strings while decoding
+///
+/// XDR strings can contain arbitrary bytes. Stellar strings such as home domains and memo texts
+/// are usually text, though.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TextValidation {
+    /// Strings can contain arbitrary bytes
+    Unchecked,
+    /// Strings must be valid UTF-8
+    Utf8,
+    /// Strings must only consist of ASCII characters
+    Ascii,
+}
+
+impl TextValidation {
+    /// Returns true if the bytes are valid text according to this validation
+    pub fn is_valid(self, bytes: &[u8]) -> bool {
+        match self {
+            TextValidation::Unchecked => true,
+            TextValidation::Utf8 => core::str::from_utf8(bytes).is_ok(),
+            TextValidation::Ascii => bytes.is_ascii(),
+        }
+    }
 }
 
 /// An helper structure for efficiently decoding XDR data
+///
+/// The source is either binary XDR or base64 encoded XDR. Base64 is decoded on the fly while
+/// reading, so that the binary XDR is never copied as a whole.
 pub struct ReadStream<T: AsRef<[u8]>> {
     read_index: usize,
     source: T,
+    base64_config: Option<Config>,
+    length: usize,
+    text_validation: TextValidation,
 }
 
 impl<T: AsRef<[u8]>> ReadStream<T> {
     /// Create a new `ReadStream` from a reference to a byte slice
     pub fn new(source: T) -> ReadStream<T> {
+        let length = source.as_ref().len();
         ReadStream {
             read_index: 0,
             source,
+            base64_config: None,
+            length,
+            text_validation: TextValidation::Unchecked,
         }
     }
 
+    /// Create a new `ReadStream` from a reference to a base64 encoded byte slice
+    ///
+    /// The base64 can be padded or unpadded. This fails with `DecodeError::InvalidBase64` if
+    /// the length of the source cannot be the length of a base64 encoding. Invalid characters
+    /// are only detected while reading.
+    pub fn new_base64(source: T, config: Config) -> Result<ReadStream<T>, DecodeError> {
+        let encoded = source.as_ref();
+        let no_of_padding_chars = encoded
+            .iter()
+            .rev()
+            .take(2)
+            .take_while(|character| **character == b'=')
+            .count();
+        if no_of_padding_chars > 0 && encoded.len() % 4 != 0 {
+            return Err(DecodeError::InvalidBase64);
+        }
+
+        let no_of_data_chars = encoded.len() - no_of_padding_chars;
+        let length = no_of_data_chars / 4 * 3
+            + match no_of_data_chars % 4 {
+                0 => 0,
+                2 => 1,
+                3 => 2,
+                _ => return Err(DecodeError::InvalidBase64),
+            };
+
+        Ok(ReadStream {
+            read_index: 0,
+            source,
+            base64_config: Some(config),
+            length,
+            text_validation: TextValidation::Unchecked,
+        })
+    }
+
+    /// Set the validation of strings that are read from this stream
+    ///
+    /// Without calling this method, strings are not validated.
+    pub fn with_text_validation(mut self, text_validation: TextValidation) -> ReadStream<T> {
+        self.text_validation = text_validation;
+        self
+    }
+
     fn ensure_size(&self, no_of_bytes_to_read: usize) -> Result<(), DecodeError> {
-        if no_of_bytes_to_read + self.read_index > self.source.as_ref().len() {
+        if no_of_bytes_to_read + self.read_index > self.length {
             return Err(self.generate_sudden_end_error(no_of_bytes_to_read));
         }
         Ok(())
@@ -95,46 +188,56 @@ impl<T: AsRef<[u8]>> ReadStream<T> {
 
     fn generate_sudden_end_error(&self, no_of_bytes_to_read: usize) -> DecodeError {
         DecodeError::SuddenEnd {
-            actual_length: self.source.as_ref().len(),
+            actual_length: self.length,
             expected_length: no_of_bytes_to_read + self.read_index,
         }
     }
 
-    fn read_next_byte_array<const N: usize>(&mut self) -> Result<&[u8; N], DecodeError> {
-        let array: Result<&[u8; N], _> =
-            (self.source.as_ref()[self.read_index..self.read_index + N]).try_into();
-
-        match array {
-            Ok(array) => {
-                self.read_index += N;
-                Ok(array)
+    /// Copy the next bytes of the binary XDR into `buffer` and advance the internal pointer
+    ///
+    /// The caller needs to ensure the size first.
+    fn read_next_bytes(&mut self, buffer: &mut [u8]) -> Result<(), DecodeError> {
+        let source = self.source.as_ref();
+        match self.base64_config {
+            None => {
+                buffer.copy_from_slice(&source[self.read_index..self.read_index + buffer.len()])
             }
-            Err(_) => Err(self.generate_sudden_end_error(N)),
+            Some(config) => decode_base64_range(source, config, self.read_index, buffer)?,
         }
+        self.read_index += buffer.len();
+        Ok(())
+    }
+
+    fn read_next_byte_array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        self.ensure_size(N)?;
+        let mut array = [0; N];
+        self.read_next_bytes(&mut array)?;
+        Ok(array)
+    }
+
+    fn skip_padding(&mut self, no_of_bytes: usize) -> Result<(), DecodeError> {
+        let no_of_padding_bytes = extend_to_multiple_of_4(no_of_bytes) - no_of_bytes;
+        self.read_next_bytes(&mut [0; 3][..no_of_padding_bytes])
     }
 
     /// Read the next big endian u32 from the stream
     pub fn read_next_u32(&mut self) -> Result<u32, DecodeError> {
-        let array: &[u8; 4] = self.read_next_byte_array()?;
-        Ok(u32::from_be_bytes(*array))
+        Ok(u32::from_be_bytes(self.read_next_byte_array()?))
     }
 
     /// Read the next big endian i32 from the stream
     pub fn read_next_i32(&mut self) -> Result<i32, DecodeError> {
-        let array: &[u8; 4] = self.read_next_byte_array()?;
-        Ok(i32::from_be_bytes(*array))
+        Ok(i32::from_be_bytes(self.read_next_byte_array()?))
     }
 
     /// Read the next big endian u64 from the stream
     pub fn read_next_u64(&mut self) -> Result<u64, DecodeError> {
-        let array: &[u8; 8] = self.read_next_byte_array()?;
-        Ok(u64::from_be_bytes(*array))
+        Ok(u64::from_be_bytes(self.read_next_byte_array()?))
     }
 
     /// Read the next big endian i64 from the stream
     pub fn read_next_i64(&mut self) -> Result<i64, DecodeError> {
-        let array: &[u8; 8] = self.read_next_byte_array()?;
-        Ok(i64::from_be_bytes(*array))
+        Ok(i64::from_be_bytes(self.read_next_byte_array()?))
     }
 
     /// Read the next array of binary data from the stream
@@ -143,14 +246,39 @@ impl<T: AsRef<[u8]>> ReadStream<T> {
     /// of the `ReadStream` is advanced by a multiple of 4.
     pub fn read_next_binary_data(&mut self, no_of_bytes: usize) -> Result<Vec<u8>, DecodeError> {
         self.ensure_size(extend_to_multiple_of_4(no_of_bytes))?;
-        let result = self.source.as_ref()[self.read_index..self.read_index + no_of_bytes].to_vec();
-        self.read_index += extend_to_multiple_of_4(no_of_bytes);
+        let mut result = sp_std::vec![0; no_of_bytes];
+        self.read_next_bytes(&mut result)?;
+        self.skip_padding(no_of_bytes)?;
         Ok(result)
     }
 
+    /// Read the next string from the stream
+    ///
+    /// This is the same as `read_next_binary_data`, but the bytes are validated according to
+    /// the `TextValidation` of the stream.
+    pub fn read_next_string(&mut self, no_of_bytes: usize) -> Result<Vec<u8>, DecodeError> {
+        let at_position = self.read_index;
+        let result = self.read_next_binary_data(no_of_bytes)?;
+        match self.text_validation.is_valid(&result) {
+            true => Ok(result),
+            false => Err(DecodeError::InvalidText { at_position }),
+        }
+    }
+
+    /// Read the next array of binary data from the stream into a buffer
+    ///
+    /// The no of bytes to read is given by the length of `buffer`. This does not
+    /// allocate. The internal pointer of the `ReadStream` is advanced by a multiple of 4.
+    pub fn read_next_binary_data_into(&mut self, buffer: &mut [u8]) -> Result<(), DecodeError> {
+        let no_of_bytes = buffer.len();
+        self.ensure_size(extend_to_multiple_of_4(no_of_bytes))?;
+        self.read_next_bytes(buffer)?;
+        self.skip_padding(no_of_bytes)
+    }
+
     /// Determine the number of bytes left to be read from the stream
     pub fn no_of_bytes_left_to_read(&self) -> isize {
-        self.source.as_ref().len() as isize - self.read_index as isize
+        self.length as isize - self.read_index as isize
     }
 
     /// Get the current pointer position of the `ReadStream`
@@ -159,9 +287,65 @@ impl<T: AsRef<[u8]>> ReadStream<T> {
     }
 }
 
+/// The number of groups of 4 base64 characters that are decoded at once
+const BASE64_GROUPS_PER_CHUNK: usize = 16;
+
+/// Decode the bytes of the base64 encoding `encoded` starting at `start` into `buffer`
+///
+/// Every group of 4 base64 characters encodes 3 bytes, so only the groups that overlap the
+/// requested range are decoded, in chunks of `BASE64_GROUPS_PER_CHUNK` groups.
+fn decode_base64_range(
+    encoded: &[u8],
+    config: Config,
+    start: usize,
+    buffer: &mut [u8],
+) -> Result<(), DecodeError> {
+    let mut chunk = [0; BASE64_GROUPS_PER_CHUNK * 3];
+    let mut group_index = start / 3;
+    let mut offset = start % 3;
+    let mut no_of_bytes_written = 0;
+
+    while no_of_bytes_written < buffer.len() {
+        let no_of_groups = min(
+            (offset + buffer.len() - no_of_bytes_written) / 3 + 1,
+            BASE64_GROUPS_PER_CHUNK,
+        );
+        let chars_start = group_index * 4;
+        let chars_end = min(chars_start + no_of_groups * 4, encoded.len());
+        let no_of_decoded_bytes =
+            decode_config_slice(&encoded[chars_start..chars_end], config, &mut chunk)
+                .map_err(|_| DecodeError::InvalidBase64)?;
+
+        // padding is only allowed at the very end
+        if (chars_end < encoded.len() && no_of_decoded_bytes != no_of_groups * 3)
+            || no_of_decoded_bytes <= offset
+        {
+            return Err(DecodeError::InvalidBase64);
+        }
+
+        let no_of_bytes = min(
+            no_of_decoded_bytes - offset,
+            buffer.len() - no_of_bytes_written,
+        );
+        buffer[no_of_bytes_written..no_of_bytes_written + no_of_bytes]
+            .copy_from_slice(&chunk[offset..offset + no_of_bytes]);
+        no_of_bytes_written += no_of_bytes;
+        group_index += no_of_groups;
+        offset = 0;
+    }
+
+    Ok(())
+}
+
 /// An helper structure for efficiently encoding XDR data
+///
+/// The result is either binary XDR or base64 encoded XDR. Base64 is encoded on the fly while
+/// writing, so that the binary XDR is never stored as a whole.
 pub struct WriteStream {
     result: Vec<u8>,
+    base64_config: Option<Config>,
+    pending_bytes: [u8; 3],
+    no_of_pending_bytes: usize,
 }
 
 impl WriteStream {
@@ -169,40 +353,96 @@ impl WriteStream {
     pub fn new() -> WriteStream {
         WriteStream {
             result: Vec::with_capacity(128),
+            base64_config: None,
+            pending_bytes: [0; 3],
+            no_of_pending_bytes: 0,
         }
     }
 
+    /// Construct a new `WriteStream` whose result is base64 encoded
+    pub fn new_base64(config: Config) -> WriteStream {
+        WriteStream {
+            base64_config: Some(config),
+            ..WriteStream::new()
+        }
+    }
+
+    fn write_next_bytes(&mut self, mut bytes: &[u8]) {
+        let config = match self.base64_config {
+            None => return self.result.extend_from_slice(bytes),
+            Some(config) => config,
+        };
+
+        // base64 encodes groups of 3 bytes, the bytes of an incomplete group are kept pending
+        if self.no_of_pending_bytes > 0 {
+            let no_of_bytes = min(3 - self.no_of_pending_bytes, bytes.len());
+            self.pending_bytes[self.no_of_pending_bytes..self.no_of_pending_bytes + no_of_bytes]
+                .copy_from_slice(&bytes[..no_of_bytes]);
+            self.no_of_pending_bytes += no_of_bytes;
+            bytes = &bytes[no_of_bytes..];
+            if self.no_of_pending_bytes < 3 {
+                return;
+            }
+
+            let pending_bytes = self.pending_bytes;
+            append_base64(&mut self.result, &pending_bytes, config);
+            self.no_of_pending_bytes = 0;
+        }
+
+        let no_of_complete_bytes = bytes.len() / 3 * 3;
+        append_base64(&mut self.result, &bytes[..no_of_complete_bytes], config);
+        let remainder = &bytes[no_of_complete_bytes..];
+        self.pending_bytes[..remainder.len()].copy_from_slice(remainder);
+        self.no_of_pending_bytes = remainder.len();
+    }
+
     /// Append a new big endian u32 to the stream
     pub fn write_next_u32(&mut self, value: u32) {
-        self.result.extend(value.to_be_bytes().iter());
+        self.write_next_bytes(&value.to_be_bytes());
     }
 
     /// Append a new big endian i32 to the stream
     pub fn write_next_i32(&mut self, value: i32) {
-        self.result.extend(value.to_be_bytes().iter());
+        self.write_next_bytes(&value.to_be_bytes());
     }
 
     /// Append a new big endian u64 to the stream
     pub fn write_next_u64(&mut self, value: u64) {
-        self.result.extend(value.to_be_bytes().iter());
+        self.write_next_bytes(&value.to_be_bytes());
     }
 
     /// Append a new big endian i64 to the stream
     pub fn write_next_i64(&mut self, value: i64) {
-        self.result.extend(value.to_be_bytes().iter());
+        self.write_next_bytes(&value.to_be_bytes());
     }
 
     /// Append an array of binary data to the stream
     pub fn write_next_binary_data(&mut self, value: &[u8]) {
-        self.result.extend_from_slice(value);
+        self.write_next_bytes(value);
         let length = value.len();
         let no_of_padding_bytes = extend_to_multiple_of_4(length) - length;
-        self.result
-            .extend(iter::repeat(0).take(no_of_padding_bytes));
+        self.write_next_bytes(&[0; 3][..no_of_padding_bytes]);
     }
 
     /// Get the result written to the stream
-    pub fn get_result(self) -> Vec<u8> {
+    ///
+    /// If the stream is base64 encoded, this encodes the last incomplete group of bytes.
+    pub fn get_result(mut self) -> Vec<u8> {
+        if let Some(config) = self.base64_config {
+            let pending_bytes = self.pending_bytes;
+            append_base64(
+                &mut self.result,
+                &pending_bytes[..self.no_of_pending_bytes],
+                config,
+            );
+        }
         self.result
     }
 }
+
+fn append_base64(result: &mut Vec<u8>, bytes: &[u8], config: Config) {
+    let start = result.len();
+    result.resize(start + bytes.len() / 3 * 4 + 4, 0);
+    let no_of_bytes_written = encode_config_slice(bytes, config, &mut result[start..]);
+    result.truncate(start + no_of_bytes_written);
+}
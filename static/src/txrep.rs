@@ -0,0 +1,822 @@
+//! SEP-0011 Txrep, the human readable representation of Stellar transactions
+//!
+//! Txrep represents a `TransactionEnvelope` as lines of the form `key: value`, see
+//! <https://github.com/stellar/stellar-protocol/blob/master/ecosystem/sep-0011.md>. The conversion
+//! uses the schema of the XDR types (see the module `dynamic`), so it covers every operation, memo
+//! and precondition of the generated XDR version.
+//!
+//! Accounts, muxed accounts and signer keys are represented as strkeys (`G...`, `M...`, `T...`,
+//! `X...`, `P...`) and assets as `XLM` or `CODE:ISSUER`. Values that cannot be represented in this
+//! compact form without changing their XDR encoding are represented field by field instead. Text
+//! after a value, e.g., a comment such as `(40.0004e7)`, is ignored.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::{convert::TryFrom, fmt};
+use sp_std::{boxed::Box, collections::btree_map::BTreeMap, vec::Vec};
+
+use crate::dynamic::{
+    decode_dynamic, decode_value, discriminant_value, encode_dynamic, encode_value, enum_value,
+    lookup, DynamicError, SchemaDefinition, SchemaType, SchemaUnionArm, XdrValue,
+    UNKNOWN_ENUM_CASE,
+};
+use crate::streams::{ReadStream, WriteStream};
+use crate::xdr::TransactionEnvelope;
+use crate::xdr_codec::{decode_hex, encode_hex, XdrCodec, HEX_DIGITS};
+
+/// An error type for converting Txrep
+#[derive(Debug)]
+pub enum TxrepError {
+    /// The line with the given number (starting at 1) is not of the form `key: value`
+    InvalidLine(usize),
+    /// The key occurs more than once
+    DuplicateKey(String),
+    /// The key is required but missing
+    MissingKey(String),
+    /// The value of the key is invalid
+    InvalidValue(String),
+    /// The key does not belong to the type
+    UnexpectedKey(String),
+    /// The value does not conform to the schema of the type
+    Dynamic(DynamicError),
+}
+
+impl From<DynamicError> for TxrepError {
+    fn from(error: DynamicError) -> Self {
+        TxrepError::Dynamic(error)
+    }
+}
+
+impl fmt::Display for TxrepError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TxrepError::InvalidLine(line) => {
+                write!(formatter, "line {} is not of the form `key: value`", line)
+            }
+            TxrepError::DuplicateKey(key) => {
+                write!(formatter, "the key `{}` occurs more than once", key)
+            }
+            TxrepError::MissingKey(key) => write!(formatter, "the key `{}` is missing", key),
+            TxrepError::InvalidValue(key) => {
+                write!(formatter, "the value of the key `{}` is invalid", key)
+            }
+            TxrepError::UnexpectedKey(key) => write!(formatter, "the key `{}` is unexpected", key),
+            TxrepError::Dynamic(error) => write!(formatter, "invalid value: {:?}", error),
+        }
+    }
+}
+
+impl TransactionEnvelope {
+    /// Returns the Txrep of this envelope
+    ///
+    /// Fails if the envelope nests values deeper than the dynamic decoder allows, see
+    /// `dynamic::MAX_DEPTH`, e.g., a claim predicate of an excessive depth.
+    pub fn to_txrep(&self) -> Result<String, TxrepError> {
+        let value = decode_dynamic("TransactionEnvelope", self.to_xdr())?;
+        render_txrep("TransactionEnvelope", &value)
+    }
+
+    /// Parses an envelope from its Txrep
+    pub fn from_txrep(txrep: &str) -> Result<Self, TxrepError> {
+        let value = parse_txrep("TransactionEnvelope", txrep)?;
+        let encoded = encode_dynamic("TransactionEnvelope", &value)?;
+        TransactionEnvelope::from_xdr(encoded).map_err(|error| DynamicError::Decode(error).into())
+    }
+}
+
+/// Render a generic value of the XDR type with the given name as Txrep
+pub fn render_txrep(type_name: &str, value: &XdrValue) -> Result<String, TxrepError> {
+    let mut output = String::new();
+    render_reference(&mut output, "", type_name, value)?;
+    Ok(output)
+}
+
+/// Parse the Txrep of a value of the XDR type with the given name
+pub fn parse_txrep(type_name: &str, txrep: &str) -> Result<XdrValue, TxrepError> {
+    let mut parser = Parser::new(txrep)?;
+    let value = parser.parse_reference("", type_name)?;
+    match parser.entries.keys().next() {
+        Some(key) => Err(TxrepError::UnexpectedKey(key.clone())),
+        None => Ok(value),
+    }
+}
+
+const STRKEY_ACCOUNT_ID: u8 = 6 << 3;
+const STRKEY_MUXED_ACCOUNT: u8 = 12 << 3;
+const STRKEY_SIGNED_PAYLOAD: u8 = 15 << 3;
+const STRKEY_PRE_AUTH_TX: u8 = 19 << 3;
+const STRKEY_HASH_X: u8 = 23 << 3;
+
+/// The union arms that are represented as strkeys: the union type, the arm and the strkey version
+///
+/// The payload of the strkey is the XDR encoding of the arm, except for muxed accounts.
+const STRKEY_ARMS: &[(&str, &str, u8)] = &[
+    ("PublicKey", "ed25519", STRKEY_ACCOUNT_ID),
+    ("MuxedAccount", "ed25519", STRKEY_ACCOUNT_ID),
+    ("MuxedAccount", "med25519", STRKEY_MUXED_ACCOUNT),
+    ("SignerKey", "ed25519", STRKEY_ACCOUNT_ID),
+    ("SignerKey", "preAuthTx", STRKEY_PRE_AUTH_TX),
+    ("SignerKey", "hashX", STRKEY_HASH_X),
+    ("SignerKey", "ed25519SignedPayload", STRKEY_SIGNED_PAYLOAD),
+];
+
+/// The unions that are represented as `XLM` or `CODE:ISSUER`
+const ASSET_TYPES: &[&str] = &["Asset", "ChangeTrustAsset", "TrustLineAsset"];
+
+/// The arms of the root envelope whose fields are represented without the prefix of the arm
+const FLATTENED_ARM_TYPES: &[&str] = &["TransactionV0Envelope", "TransactionV1Envelope"];
+
+fn join_key(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+fn is_flattened(schema_type: &SchemaType) -> bool {
+    matches!(schema_type, SchemaType::Reference(name) if FLATTENED_ARM_TYPES.contains(name))
+}
+
+/// Follow typedefs of other named types, e.g., from `AccountID` to `PublicKey`
+fn resolve_typedefs(type_name: &'static str) -> Result<&'static str, TxrepError> {
+    match lookup(type_name)? {
+        SchemaDefinition::Typedef(SchemaType::Reference(inner_name)) => {
+            resolve_typedefs(inner_name)
+        }
+        _ => Ok(type_name),
+    }
+}
+
+fn find_arm_by_name(
+    type_name: &str,
+    arm_name: &str,
+) -> Result<Option<&'static SchemaUnionArm>, TxrepError> {
+    Ok(match lookup(type_name)? {
+        SchemaDefinition::Union { arms, .. } => arms
+            .iter()
+            .find(|arm| matches!(arm.arm, Some((name, _)) if name == arm_name)),
+        _ => None,
+    })
+}
+
+/// Construct the discriminant of a union from its integer value
+fn discriminant_for(switch: &SchemaType, discriminant: i64) -> Result<XdrValue, TxrepError> {
+    match switch {
+        SchemaType::Int => Ok(XdrValue::Int(discriminant as i32)),
+        SchemaType::UnsignedInt => Ok(XdrValue::UnsignedInt(discriminant as u32)),
+        SchemaType::Bool => Ok(XdrValue::Bool(discriminant != 0)),
+        SchemaType::Reference(type_name) => match lookup(type_name)? {
+            SchemaDefinition::Enum { cases, open } => i32::try_from(discriminant)
+                .ok()
+                .and_then(|value| enum_value(cases, *open, value))
+                .ok_or(TxrepError::Dynamic(DynamicError::InvalidValue)),
+            SchemaDefinition::Typedef(inner_type) => discriminant_for(inner_type, discriminant),
+            _ => Err(DynamicError::InvalidValue.into()),
+        },
+        _ => Err(DynamicError::InvalidValue.into()),
+    }
+}
+
+/// Construct a value of a union type of the given arm
+fn union_value(
+    type_name: &str,
+    arm: &SchemaUnionArm,
+    value: Option<XdrValue>,
+) -> Result<XdrValue, TxrepError> {
+    let switch = match lookup(type_name)? {
+        SchemaDefinition::Union { switch, .. } => switch,
+        _ => return Err(DynamicError::InvalidValue.into()),
+    };
+
+    Ok(XdrValue::Union {
+        discriminant: Box::new(discriminant_for(switch, arm.discriminant)?),
+        arm: match (arm.arm, value) {
+            (Some((arm_name, _)), Some(value)) => Some((arm_name, Box::new(value))),
+            (None, None) => None,
+            _ => return Err(DynamicError::InvalidValue.into()),
+        },
+    })
+}
+
+fn render_line(output: &mut String, key: &str, value: &str) {
+    output.push_str(key);
+    output.push_str(": ");
+    output.push_str(value);
+    output.push('\n');
+}
+
+fn render_value(
+    output: &mut String,
+    key: &str,
+    schema_type: &SchemaType,
+    value: &XdrValue,
+) -> Result<(), TxrepError> {
+    match (schema_type, value) {
+        (SchemaType::Int, XdrValue::Int(value)) => render_line(output, key, &value.to_string()),
+        (SchemaType::UnsignedInt, XdrValue::UnsignedInt(value)) => {
+            render_line(output, key, &value.to_string())
+        }
+        (SchemaType::Hyper, XdrValue::Hyper(value)) => render_line(output, key, &value.to_string()),
+        (SchemaType::UnsignedHyper, XdrValue::UnsignedHyper(value)) => {
+            render_line(output, key, &value.to_string())
+        }
+        (SchemaType::Bool, XdrValue::Bool(value)) => render_line(output, key, &value.to_string()),
+        (SchemaType::Opaque(_), XdrValue::Opaque(bytes))
+        | (SchemaType::VarOpaque(_), XdrValue::Opaque(bytes)) => {
+            render_line(output, key, &encode_hex(bytes))
+        }
+        (SchemaType::String(_), XdrValue::String(bytes)) => {
+            render_line(output, key, &encode_string(bytes))
+        }
+        (SchemaType::Array(inner_type, _), XdrValue::Array(values)) => {
+            for (index, value) in values.iter().enumerate() {
+                render_value(output, &format!("{}[{}]", key, index), inner_type, value)?;
+            }
+        }
+        (SchemaType::VarArray(inner_type, _), XdrValue::Array(values)) => {
+            render_line(output, &join_key(key, "len"), &values.len().to_string());
+            for (index, value) in values.iter().enumerate() {
+                render_value(output, &format!("{}[{}]", key, index), inner_type, value)?;
+            }
+        }
+        (SchemaType::Optional(inner_type), XdrValue::Optional(value)) => {
+            render_line(
+                output,
+                &join_key(key, "_present"),
+                &value.is_some().to_string(),
+            );
+            if let Some(value) = value {
+                render_value(output, key, inner_type, value)?;
+            }
+        }
+        (SchemaType::Reference(type_name), value) => {
+            render_reference(output, key, type_name, value)?
+        }
+        _ => return Err(TxrepError::InvalidValue(key.to_string())),
+    }
+
+    Ok(())
+}
+
+fn render_reference(
+    output: &mut String,
+    key: &str,
+    type_name: &str,
+    value: &XdrValue,
+) -> Result<(), TxrepError> {
+    if !key.is_empty() {
+        if let Some(compact) = render_compact(type_name, value)? {
+            render_line(output, key, &compact);
+            return Ok(());
+        }
+    }
+
+    match (lookup(type_name)?, value) {
+        (SchemaDefinition::Typedef(schema_type), value) => {
+            render_value(output, key, schema_type, value)?
+        }
+        (SchemaDefinition::Enum { .. }, XdrValue::Enum { name, value }) => {
+            if *name == UNKNOWN_ENUM_CASE {
+                render_line(output, key, &value.to_string())
+            } else {
+                render_line(output, key, name)
+            }
+        }
+        (SchemaDefinition::Struct(fields), XdrValue::Struct(values))
+            if fields.len() == values.len() =>
+        {
+            for ((name, schema_type), (value_name, value)) in fields.iter().zip(values.iter()) {
+                if name != value_name {
+                    return Err(TxrepError::InvalidValue(join_key(key, value_name)));
+                }
+                render_value(output, &join_key(key, name), schema_type, value)?;
+            }
+        }
+        (
+            SchemaDefinition::Union {
+                switch_name,
+                switch,
+                arms,
+                ..
+            },
+            XdrValue::Union { discriminant, arm },
+        ) => {
+            render_value(output, &join_key(key, switch_name), switch, discriminant)?;
+            if let Some((arm_name, value)) = arm {
+                let arm_type = arms
+                    .iter()
+                    .find_map(|arm| match arm.arm {
+                        Some((name, schema_type)) if name == *arm_name => Some(schema_type),
+                        _ => None,
+                    })
+                    .ok_or_else(|| TxrepError::InvalidValue(join_key(key, arm_name)))?;
+
+                let arm_key = if is_flattened(&arm_type) {
+                    key.to_string()
+                } else {
+                    join_key(key, arm_name)
+                };
+                render_value(output, &arm_key, &arm_type, value)?;
+            }
+        }
+        _ => return Err(TxrepError::InvalidValue(key.to_string())),
+    }
+
+    Ok(())
+}
+
+/// Render a value as a strkey or asset, if this does not change its XDR encoding
+fn render_compact(type_name: &str, value: &XdrValue) -> Result<Option<String>, TxrepError> {
+    if ASSET_TYPES.contains(&type_name) {
+        return render_asset(value);
+    }
+
+    render_strkey(type_name, value)
+}
+
+fn render_strkey(type_name: &str, value: &XdrValue) -> Result<Option<String>, TxrepError> {
+    let (arm_name, value) = match value {
+        XdrValue::Union {
+            arm: Some((arm_name, value)),
+            ..
+        } => (*arm_name, value),
+        _ => return Ok(None),
+    };
+
+    let version = match STRKEY_ARMS
+        .iter()
+        .find(|(strkey_type, strkey_arm, _)| *strkey_type == type_name && *strkey_arm == arm_name)
+    {
+        Some((_, _, version)) => *version,
+        None => return Ok(None),
+    };
+    let arm_type = match find_arm_by_name(type_name, arm_name)? {
+        Some(SchemaUnionArm {
+            arm: Some((_, arm_type)),
+            ..
+        }) => arm_type,
+        _ => return Ok(None),
+    };
+
+    let mut write_stream = WriteStream::new();
+    encode_value(arm_type, value, &mut write_stream)?;
+    let mut payload = write_stream.get_result();
+    if version == STRKEY_MUXED_ACCOUNT {
+        // the XDR encoding starts with the id, the strkey with the key
+        payload.rotate_left(8);
+    }
+
+    Ok(Some(encode_strkey(version, &payload)))
+}
+
+fn render_asset(value: &XdrValue) -> Result<Option<String>, TxrepError> {
+    match value {
+        XdrValue::Union {
+            discriminant,
+            arm: None,
+        } if matches!(
+            **discriminant,
+            XdrValue::Enum {
+                name: "ASSET_TYPE_NATIVE",
+                ..
+            }
+        ) =>
+        {
+            Ok(Some("XLM".to_string()))
+        }
+        XdrValue::Union {
+            arm: Some((arm_name, value)),
+            ..
+        } if *arm_name == "alphaNum4" || *arm_name == "alphaNum12" => {
+            let (code, issuer) = match &**value {
+                XdrValue::Struct(fields) if fields.len() == 2 => match (&fields[0].1, &fields[1].1)
+                {
+                    (XdrValue::Opaque(code), issuer) => (code, issuer),
+                    _ => return Ok(None),
+                },
+                _ => return Ok(None),
+            };
+
+            let code_length = code
+                .iter()
+                .rposition(|byte| *byte != 0)
+                .map_or(0, |index| index + 1);
+            let code = &code[..code_length];
+            let valid_length = if *arm_name == "alphaNum4" {
+                code_length >= 1
+            } else {
+                code_length > 4
+            };
+            if !valid_length || !code.iter().all(u8::is_ascii_alphanumeric) {
+                return Ok(None);
+            }
+
+            Ok(render_strkey("PublicKey", issuer)?.map(|issuer| {
+                let code: String = code.iter().map(|byte| *byte as char).collect();
+                format!("{}:{}", code, issuer)
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+struct Parser<'a> {
+    entries: BTreeMap<String, &'a str>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(txrep: &'a str) -> Result<Self, TxrepError> {
+        let mut entries = BTreeMap::new();
+        for (index, line) in txrep.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let separator = line.find(':').ok_or(TxrepError::InvalidLine(index + 1))?;
+            let key = line[..separator].trim();
+            if key.is_empty() {
+                return Err(TxrepError::InvalidLine(index + 1));
+            }
+            if entries
+                .insert(key.to_string(), line[separator + 1..].trim())
+                .is_some()
+            {
+                return Err(TxrepError::DuplicateKey(key.to_string()));
+            }
+        }
+
+        Ok(Parser { entries })
+    }
+
+    fn take(&mut self, key: &str) -> Result<&'a str, TxrepError> {
+        self.entries
+            .remove(key)
+            .ok_or_else(|| TxrepError::MissingKey(key.to_string()))
+    }
+
+    /// Returns the value of the key without a trailing comment
+    fn take_token(&mut self, key: &str) -> Result<&'a str, TxrepError> {
+        Ok(self.take(key)?.split_whitespace().next().unwrap_or(""))
+    }
+
+    fn take_parsed<T: core::str::FromStr>(&mut self, key: &str) -> Result<T, TxrepError> {
+        self.take_token(key)?
+            .parse()
+            .map_err(|_| TxrepError::InvalidValue(key.to_string()))
+    }
+
+    fn parse_value(&mut self, key: &str, schema_type: &SchemaType) -> Result<XdrValue, TxrepError> {
+        let invalid_value = || TxrepError::InvalidValue(key.to_string());
+
+        Ok(match schema_type {
+            SchemaType::Int => XdrValue::Int(self.take_parsed(key)?),
+            SchemaType::UnsignedInt => XdrValue::UnsignedInt(self.take_parsed(key)?),
+            SchemaType::Hyper => XdrValue::Hyper(self.take_parsed(key)?),
+            SchemaType::UnsignedHyper => XdrValue::UnsignedHyper(self.take_parsed(key)?),
+            SchemaType::Bool => XdrValue::Bool(self.take_parsed(key)?),
+            SchemaType::Opaque(length) => {
+                let bytes =
+                    decode_hex(self.take_token(key)?.as_bytes()).ok_or_else(invalid_value)?;
+                if bytes.len() != *length as usize {
+                    return Err(invalid_value());
+                }
+                XdrValue::Opaque(bytes)
+            }
+            SchemaType::VarOpaque(max_length) => {
+                let bytes =
+                    decode_hex(self.take_token(key)?.as_bytes()).ok_or_else(invalid_value)?;
+                if bytes.len() > *max_length as usize {
+                    return Err(invalid_value());
+                }
+                XdrValue::Opaque(bytes)
+            }
+            SchemaType::String(max_length) => {
+                let bytes = decode_string(self.take(key)?).ok_or_else(invalid_value)?;
+                if bytes.len() > *max_length as usize {
+                    return Err(invalid_value());
+                }
+                XdrValue::String(bytes)
+            }
+            SchemaType::Array(inner_type, length) => {
+                let mut values = Vec::with_capacity(*length as usize);
+                for index in 0..*length {
+                    values.push(self.parse_value(&format!("{}[{}]", key, index), inner_type)?);
+                }
+                XdrValue::Array(values)
+            }
+            SchemaType::VarArray(inner_type, max_length) => {
+                let length_key = join_key(key, "len");
+                let length: usize = self.take_parsed(&length_key)?;
+                if length > *max_length as usize {
+                    return Err(TxrepError::InvalidValue(length_key));
+                }
+                let mut values = Vec::with_capacity(length);
+                for index in 0..length {
+                    values.push(self.parse_value(&format!("{}[{}]", key, index), inner_type)?);
+                }
+                XdrValue::Array(values)
+            }
+            SchemaType::Optional(inner_type) => {
+                match self.take_parsed(&join_key(key, "_present"))? {
+                    true => XdrValue::Optional(Some(Box::new(self.parse_value(key, inner_type)?))),
+                    false => XdrValue::Optional(None),
+                }
+            }
+            SchemaType::Reference(type_name) => self.parse_reference(key, type_name)?,
+        })
+    }
+
+    fn parse_reference(&mut self, key: &str, type_name: &str) -> Result<XdrValue, TxrepError> {
+        let is_compact = ASSET_TYPES.contains(&type_name)
+            || STRKEY_ARMS
+                .iter()
+                .any(|(strkey_type, _, _)| *strkey_type == type_name);
+        if is_compact && self.entries.contains_key(key) {
+            let text = self.take_token(key)?;
+            return if ASSET_TYPES.contains(&type_name) {
+                parse_asset(key, type_name, text)
+            } else {
+                parse_strkey(key, type_name, text)
+            };
+        }
+
+        match lookup(type_name)? {
+            SchemaDefinition::Typedef(schema_type) => self.parse_value(key, schema_type),
+            SchemaDefinition::Enum { cases, open } => {
+                let name = self.take_token(key)?;
+                match cases.iter().find(|case| case.name == name) {
+                    Some(case) => Ok(XdrValue::Enum {
+                        name: case.name,
+                        value: case.value,
+                    }),
+                    // unknown values of open enums are represented by their integer value
+                    None => name
+                        .parse::<i32>()
+                        .ok()
+                        .and_then(|value| enum_value(cases, *open, value))
+                        .ok_or_else(|| TxrepError::InvalidValue(key.to_string())),
+                }
+            }
+            SchemaDefinition::Struct(fields) => {
+                let mut values = Vec::with_capacity(fields.len());
+                for (name, schema_type) in fields.iter() {
+                    values.push((*name, self.parse_value(&join_key(key, name), schema_type)?));
+                }
+                Ok(XdrValue::Struct(values))
+            }
+            SchemaDefinition::Union {
+                switch_name,
+                switch,
+                arms,
+                has_default_arm,
+            } => {
+                let switch_key = join_key(key, switch_name);
+                let discriminant = self.parse_value(&switch_key, switch)?;
+                let discriminant_value = discriminant_value(&discriminant)
+                    .ok_or_else(|| TxrepError::InvalidValue(switch_key.clone()))?;
+
+                let arm = match arms
+                    .iter()
+                    .find(|arm| arm.discriminant == discriminant_value)
+                {
+                    Some(SchemaUnionArm {
+                        arm: Some((arm_name, arm_type)),
+                        ..
+                    }) => {
+                        let arm_key = if is_flattened(arm_type) {
+                            key.to_string()
+                        } else {
+                            join_key(key, arm_name)
+                        };
+                        Some((*arm_name, Box::new(self.parse_value(&arm_key, arm_type)?)))
+                    }
+                    Some(SchemaUnionArm { arm: None, .. }) => None,
+                    None if *has_default_arm => None,
+                    None => return Err(TxrepError::InvalidValue(switch_key)),
+                };
+
+                Ok(XdrValue::Union {
+                    discriminant: Box::new(discriminant),
+                    arm,
+                })
+            }
+        }
+    }
+}
+
+fn parse_strkey(key: &str, type_name: &str, text: &str) -> Result<XdrValue, TxrepError> {
+    for (strkey_type, arm_name, version) in STRKEY_ARMS.iter() {
+        if *strkey_type != type_name {
+            continue;
+        }
+
+        let mut payload = match decode_strkey(*version, text) {
+            Some(payload) => payload,
+            None => continue,
+        };
+        if *version == STRKEY_MUXED_ACCOUNT {
+            if payload.len() != 40 {
+                break;
+            }
+            payload.rotate_right(8);
+        }
+
+        let arm = match find_arm_by_name(type_name, arm_name)? {
+            Some(arm) => arm,
+            None => continue,
+        };
+        let arm_type = match arm.arm {
+            Some((_, arm_type)) => arm_type,
+            None => continue,
+        };
+
+        let mut read_stream = ReadStream::new(&payload);
+        let value = match decode_value(&arm_type, &mut read_stream) {
+            Ok(value) if read_stream.no_of_bytes_left_to_read() == 0 => value,
+            _ => break,
+        };
+        return union_value(type_name, arm, Some(value));
+    }
+
+    Err(TxrepError::InvalidValue(key.to_string()))
+}
+
+fn parse_asset(key: &str, type_name: &str, text: &str) -> Result<XdrValue, TxrepError> {
+    let invalid_value = || TxrepError::InvalidValue(key.to_string());
+
+    let arms = match lookup(type_name)? {
+        SchemaDefinition::Union { arms, .. } => arms,
+        _ => return Err(invalid_value()),
+    };
+
+    if text == "XLM" {
+        let arm = arms
+            .iter()
+            .find(|arm| arm.case_name == "ASSET_TYPE_NATIVE")
+            .ok_or_else(invalid_value)?;
+        return union_value(type_name, arm, None);
+    }
+
+    let separator = text.find(':').ok_or_else(invalid_value)?;
+    let (code, issuer) = (&text[..separator], &text[separator + 1..]);
+    if code.is_empty() || code.len() > 12 || !code.bytes().all(|byte| byte.is_ascii_alphanumeric())
+    {
+        return Err(invalid_value());
+    }
+
+    let (arm_name, code_length) = if code.len() <= 4 {
+        ("alphaNum4", 4)
+    } else {
+        ("alphaNum12", 12)
+    };
+    let arm = find_arm_by_name(type_name, arm_name)?.ok_or_else(invalid_value)?;
+    let fields = match arm.arm {
+        Some((_, SchemaType::Reference(arm_type_name))) => match lookup(arm_type_name)? {
+            SchemaDefinition::Struct(fields) if fields.len() == 2 => fields,
+            _ => return Err(invalid_value()),
+        },
+        _ => return Err(invalid_value()),
+    };
+    let issuer_type = match fields[1].1 {
+        SchemaType::Reference(issuer_type) => resolve_typedefs(issuer_type)?,
+        _ => return Err(invalid_value()),
+    };
+
+    let mut code_bytes = code.as_bytes().to_vec();
+    code_bytes.resize(code_length, 0);
+    let value = XdrValue::Struct(alloc::vec![
+        (fields[0].0, XdrValue::Opaque(code_bytes)),
+        (fields[1].0, parse_strkey(key, issuer_type, issuer)?),
+    ]);
+
+    union_value(type_name, arm, Some(value))
+}
+
+/// Render a string in double quotes, escaping all bytes that are not printable ASCII
+fn encode_string(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() + 2);
+    result.push('"');
+    for byte in bytes {
+        match byte {
+            b'"' => result.push_str("\\\""),
+            b'\\' => result.push_str("\\\\"),
+            0x20..=0x7e => result.push(*byte as char),
+            _ => {
+                result.push_str("\\x");
+                result.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+                result.push(HEX_DIGITS[(byte & 0xf) as usize] as char);
+            }
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Parse a string in double quotes, ignoring any text after the closing quote
+fn decode_string(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = text.as_bytes().iter();
+    if bytes.next() != Some(&b'"') {
+        return None;
+    }
+
+    let mut result = Vec::new();
+    loop {
+        match *bytes.next()? {
+            b'"' => return Some(result),
+            b'\\' => match *bytes.next()? {
+                b'"' => result.push(b'"'),
+                b'\\' => result.push(b'\\'),
+                b'n' => result.push(b'\n'),
+                b'r' => result.push(b'\r'),
+                b't' => result.push(b'\t'),
+                b'x' => {
+                    let high = (*bytes.next()? as char).to_digit(16)?;
+                    let low = (*bytes.next()? as char).to_digit(16)?;
+                    result.push((high << 4 | low) as u8);
+                }
+                _ => return None,
+            },
+            byte => result.push(byte),
+        }
+    }
+}
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// The CRC16-XModem checksum of strkeys
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                crc << 1 ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn encode_strkey(version: u8, payload: &[u8]) -> String {
+    let mut data = Vec::with_capacity(payload.len() + 3);
+    data.push(version);
+    data.extend_from_slice(payload);
+    let checksum = crc16(&data);
+    data.extend_from_slice(&checksum.to_le_bytes());
+
+    let mut result = String::with_capacity(data.len() * 8 / 5 + 1);
+    let mut buffer: u32 = 0;
+    let mut no_of_bits = 0;
+    for byte in data {
+        buffer = buffer << 8 | byte as u32;
+        no_of_bits += 8;
+        while no_of_bits >= 5 {
+            no_of_bits -= 5;
+            result.push(BASE32_ALPHABET[(buffer >> no_of_bits & 0x1f) as usize] as char);
+        }
+    }
+    if no_of_bits > 0 {
+        result.push(BASE32_ALPHABET[(buffer << (5 - no_of_bits) & 0x1f) as usize] as char);
+    }
+    result
+}
+
+/// Decode a strkey of the given version and return its payload
+///
+/// Only the canonical encoding of a payload is accepted.
+fn decode_strkey(version: u8, text: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut no_of_bits = 0;
+    for character in text.bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|digit| *digit == character)?;
+        buffer = buffer << 5 | value as u32;
+        no_of_bits += 5;
+        if no_of_bits >= 8 {
+            no_of_bits -= 8;
+            data.push((buffer >> no_of_bits) as u8);
+        }
+    }
+
+    if data.len() < 3 || data[0] != version {
+        return None;
+    }
+    let checksum_start = data.len() - 2;
+    if crc16(&data[..checksum_start]).to_le_bytes() != data[checksum_start..] {
+        return None;
+    }
+
+    let payload = data[1..checksum_start].to_vec();
+    if encode_strkey(version, &payload) != text {
+        return None;
+    }
+    Some(payload)
+}
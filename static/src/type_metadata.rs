@@ -0,0 +1,70 @@
+//! Runtime metadata of the XDR types
+//!
+//! This allows generic tooling (loggers, explorers, validators) to work with any XDR type
+//! without hard-coding the types.
+
+/// The kind of an XDR type
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum XdrTypeKind {
+    Struct,
+    Enum,
+    Union,
+    Typedef,
+}
+
+/// Metadata of a case of an XDR enum
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct EnumCaseMetadata {
+    /// The name of the case as in the XDR definition, e.g., `MEMO_TEXT`
+    pub name: &'static str,
+    /// The integer value of the case
+    pub value: i32,
+}
+
+/// Metadata of an arm of an XDR union
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct UnionArmMetadata {
+    /// The name of the discriminant case as in the XDR definition, e.g., `MEMO_TEXT`
+    ///
+    /// If the union switches on an integer, then this is the decimal representation of the
+    /// discriminant.
+    pub case_name: &'static str,
+    /// The value of the discriminant
+    pub discriminant: i64,
+    /// The name of the arm as in the XDR definition, e.g., `text`; `None` if the arm is void
+    pub arm_name: Option<&'static str>,
+}
+
+/// Metadata of an XDR type
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct XdrTypeMetadata {
+    /// The name of the type as in the XDR definition
+    pub name: &'static str,
+    /// The kind of the type
+    pub kind: XdrTypeKind,
+    /// The names of the fields in the order of the XDR definition (only for structs)
+    pub fields: &'static [&'static str],
+    /// The arms of the union in the order of the XDR definition (only for unions)
+    pub union_arms: &'static [UnionArmMetadata],
+    /// The cases of the enum in the order of the XDR definition (only for enums)
+    pub enum_cases: &'static [EnumCaseMetadata],
+}
+
+/// Trait for XDR types that expose their metadata at runtime
+///
+/// Types that are mere type aliases of other types (XDR typedefs) do not implement this trait,
+/// unless typedefs are generated as newtypes. Their metadata is available via `xdr::type_metadata`.
+pub trait XdrType {
+    /// Returns the metadata of this type
+    fn metadata() -> &'static XdrTypeMetadata;
+
+    /// Returns the name of this type as in the XDR definition
+    fn xdr_name() -> &'static str {
+        Self::metadata().name
+    }
+
+    /// Returns the kind of this type
+    fn xdr_kind() -> XdrTypeKind {
+        Self::metadata().kind
+    }
+}
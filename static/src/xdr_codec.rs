@@ -1,10 +1,15 @@
 //! Trait for types that can be XDR encoded/decoded
 
-use base64::{decode_config_slice, encode_config_slice};
-use core::convert::{AsRef, TryInto};
+use alloc::string::String;
+use base64::Config;
+use core::convert::AsRef;
 use sp_std::{boxed::Box, vec::Vec};
 
-use crate::streams::{DecodeError, ReadStream, WriteStream};
+use crate::streams::{DecodeError, ReadStream, TextValidation, WriteStream};
+
+/// Derive macro for `XdrCodec`, see the crate `substrate-stellar-xdr-derive`
+#[cfg(feature = "derive")]
+pub use substrate_stellar_xdr_derive::XdrCodec;
 
 /// The XDR decoder/encoder trait
 ///
@@ -23,63 +28,144 @@ pub trait XdrCodec: Sized {
     ///
     /// This will return error if decoding was not successful
     fn from_xdr<T: AsRef<[u8]>>(input: T) -> Result<Self, DecodeError> {
-        let mut read_stream = ReadStream::new(input);
-        let value = Self::from_xdr_buffered(&mut read_stream)?;
-        if read_stream.no_of_bytes_left_to_read() != 0 {
-            return Err(DecodeError::TypeEndsTooEarly {
-                remaining_no_of_bytes: read_stream.no_of_bytes_left_to_read(),
-            });
-        }
+        decode_to_end(ReadStream::new(input))
+    }
 
-        Ok(value)
+    /// Decode XDR provided as a reference to a byte vector and validate all strings
+    ///
+    /// This is the same as `from_xdr`, but decoding fails with `DecodeError::InvalidText` if a
+    /// string is not valid text according to `text_validation`.
+    fn from_xdr_with_text_validation<T: AsRef<[u8]>>(
+        input: T,
+        text_validation: TextValidation,
+    ) -> Result<Self, DecodeError> {
+        decode_to_end(ReadStream::new(input).with_text_validation(text_validation))
     }
 
     /// Encode this type as base64 encoded XDR
     ///
     /// This returns an ASCII string (as a byte vector) that is the base64 encoding
-    /// of the XDR encoding of this type.
+    /// of the XDR encoding of this type. The base64 is encoded while encoding the XDR.
     fn to_base64_xdr(&self) -> Vec<u8> {
-        let xdr = self.to_xdr();
-        let mut base64_buffer = Vec::new();
-        base64_buffer.resize(xdr.len() * 4 / 3 + 4, 0);
-        let bytes_written = encode_config_slice(xdr, base64::STANDARD, &mut base64_buffer);
-        base64_buffer.resize(bytes_written, 0);
-        base64_buffer
+        encode_base64(self, base64::STANDARD)
+    }
+
+    /// Encode this type as base64 encoded XDR and return it as a `String`
+    fn to_base64_xdr_string(&self) -> String {
+        ascii_to_string(self.to_base64_xdr())
     }
 
     /// Decode this type from base64 encoded XDR
     ///
-    /// This takes a reference to an ASCII string (as a byte vector), decodes it as base64
-    /// and then decodes the resulting binary array as XDR.
+    /// This takes a reference to an ASCII string (as a byte vector) and decodes it as
+    /// XDR, decoding the base64 on the fly without an intermediate buffer.
     fn from_base64_xdr<T: AsRef<[u8]>>(input: T) -> Result<Self, DecodeError> {
-        let input = input.as_ref();
-        let mut buf = Vec::new();
-        buf.resize(input.len() * 4 / 3 + 4, 0);
-
-        match decode_config_slice(input, base64::STANDARD, &mut buf) {
-            Ok(bytes_written) => {
-                buf.resize(bytes_written, 0);
-                Self::from_xdr(buf)
-            }
-            Err(_) => Err(DecodeError::InvalidBase64),
-        }
+        decode_to_end(ReadStream::new_base64(input, base64::STANDARD)?)
+    }
+
+    /// Encode this type as URL-safe base64 encoded XDR without padding
+    ///
+    /// This uses the alphabet with `-` and `_` instead of `+` and `/` as required for URIs
+    /// such as those of SEP-0007.
+    fn to_base64_url_xdr(&self) -> Vec<u8> {
+        encode_base64(self, base64::URL_SAFE_NO_PAD)
+    }
+
+    /// Encode this type as URL-safe base64 encoded XDR and return it as a `String`
+    fn to_base64_url_xdr_string(&self) -> String {
+        ascii_to_string(self.to_base64_url_xdr())
+    }
+
+    /// Decode this type from URL-safe base64 encoded XDR
+    ///
+    /// The input can be padded or unpadded.
+    fn from_base64_url_xdr<T: AsRef<[u8]>>(input: T) -> Result<Self, DecodeError> {
+        decode_to_end(ReadStream::new_base64(input, base64::URL_SAFE_NO_PAD)?)
+    }
+
+    /// Encode this type as hex encoded XDR
+    ///
+    /// This returns an ASCII string (as a byte vector) of lower case hex digits.
+    fn to_hex_xdr(&self) -> Vec<u8> {
+        encode_hex(&self.to_xdr()).into_bytes()
+    }
+
+    /// Encode this type as hex encoded XDR and return it as a `String`
+    fn to_hex_xdr_string(&self) -> String {
+        encode_hex(&self.to_xdr())
+    }
+
+    /// Decode this type from hex encoded XDR
+    ///
+    /// The hex digits can be lower or upper case.
+    fn from_hex_xdr<T: AsRef<[u8]>>(input: T) -> Result<Self, DecodeError> {
+        Self::from_xdr(decode_hex(input.as_ref()).ok_or(DecodeError::InvalidHex)?)
     }
 
     /// Encode the XDR to a write stream
     ///
-    /// This is the basic implementation of the XDR encoder of this type. The methods
-    /// `to_xdr` and `to_base64_xdr` call this function to do the heavy lifting.
+    /// This is the basic implementation of the XDR encoder of this type. The method
+    /// `to_xdr` and all transport encodings call this function to do the heavy lifting.
     fn to_xdr_buffered(&self, write_stream: &mut WriteStream);
 
     /// Decode the XDR from a read stream
     ///
-    /// This is the basic implementation of the XDR decoder of this type. The methods
-    /// `from_xdr` and `from_base64_xdr` call this function to do the heavy lifting.
+    /// This is the basic implementation of the XDR decoder of this type. The method
+    /// `from_xdr` and all transport encodings call this function to do the heavy lifting.
     fn from_xdr_buffered<T: AsRef<[u8]>>(
         read_stream: &mut ReadStream<T>,
     ) -> Result<Self, DecodeError>;
 }
 
+pub(crate) const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Encode bytes as lower case hex digits, the transport encoding of `XdrCodec::to_hex_xdr`
+pub fn encode_hex(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        result.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+        result.push(HEX_DIGITS[(byte & 0xf) as usize] as char);
+    }
+    result
+}
+
+/// Decode lower or upper case hex digits, returns `None` for invalid digits or an odd length
+pub fn decode_hex(text: &[u8]) -> Option<Vec<u8>> {
+    let pairs = text.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return None;
+    }
+
+    let digit = |character: u8| (character as char).to_digit(16).map(|digit| digit as u8);
+    pairs
+        .map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?))
+        .collect()
+}
+
+fn encode_base64<V: XdrCodec>(value: &V, config: Config) -> Vec<u8> {
+    let mut write_stream = WriteStream::new_base64(config);
+    value.to_xdr_buffered(&mut write_stream);
+    write_stream.get_result()
+}
+
+fn decode_to_end<V: XdrCodec, T: AsRef<[u8]>>(
+    mut read_stream: ReadStream<T>,
+) -> Result<V, DecodeError> {
+    let value = V::from_xdr_buffered(&mut read_stream)?;
+    if read_stream.no_of_bytes_left_to_read() != 0 {
+        return Err(DecodeError::TypeEndsTooEarly {
+            remaining_no_of_bytes: read_stream.no_of_bytes_left_to_read(),
+        });
+    }
+
+    Ok(value)
+}
+
+fn ascii_to_string(ascii: Vec<u8>) -> String {
+    // base64 and hex encodings only consist of ASCII characters
+    String::from_utf8(ascii).expect("the encoding is ASCII")
+}
+
 /// Implementation of the XDR decoder/encoder for `u64`
 impl XdrCodec for u64 {
     fn to_xdr_buffered(&self, write_stream: &mut WriteStream) {
@@ -155,7 +241,8 @@ impl XdrCodec for bool {
 
 /// Implementation of the XDR decoder/encoder for a fixed size array
 ///
-/// This requires that the inner type already implements `XdrCodec`
+/// This requires that the inner type already implements `XdrCodec`. Decoding
+/// does not allocate on the heap: the elements are decoded directly into the array.
 impl<T: XdrCodec, const N: usize> XdrCodec for [T; N] {
     fn to_xdr_buffered(&self, write_stream: &mut WriteStream) {
         for item in self.iter() {
@@ -166,15 +253,28 @@ impl<T: XdrCodec, const N: usize> XdrCodec for [T; N] {
     fn from_xdr_buffered<R: AsRef<[u8]>>(
         read_stream: &mut ReadStream<R>,
     ) -> Result<Self, DecodeError> {
-        let mut result = Vec::<T>::with_capacity(N);
-        for _ in 0..N {
-            result.push(T::from_xdr_buffered(read_stream)?)
+        let mut error = None;
+        let result = [(); N].map(|_| match error {
+            Some(_) => None,
+            None => match T::from_xdr_buffered(read_stream) {
+                Ok(item) => Some(item),
+                Err(decode_error) => {
+                    error = Some(decode_error);
+                    None
+                }
+            },
+        });
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(result.map(|item| item.unwrap())),
         }
-        result.try_into().map_err(|_| unreachable!())
     }
 }
 
 /// Implementation of the XDR decoder/encoder for fixed length binary data
+///
+/// Decoding does not allocate on the heap: the bytes are copied directly into the array.
 impl<const N: usize> XdrCodec for [u8; N] {
     fn to_xdr_buffered(&self, write_stream: &mut WriteStream) {
         write_stream.write_next_binary_data(self);
@@ -183,8 +283,9 @@ impl<const N: usize> XdrCodec for [u8; N] {
     fn from_xdr_buffered<T: AsRef<[u8]>>(
         read_stream: &mut ReadStream<T>,
     ) -> Result<Self, DecodeError> {
-        let value = read_stream.read_next_binary_data(N)?;
-        value.try_into().map_err(|_| unreachable!())
+        let mut result = [0u8; N];
+        read_stream.read_next_binary_data_into(&mut result)?;
+        Ok(result)
     }
 }
 
@@ -0,0 +1,5 @@
+//! Tests of a crate generated with `OPEN_ENUMS=OperationType,OperationResultCode,LedgerEntryType`,
+//! see `npm run test-open-enums`
+
+#[cfg(test)]
+mod open_enums;
@@ -0,0 +1,84 @@
+//! Tests of decoding and encoding undefined values of open enums with the generated types
+
+use substrate_stellar_xdr::{
+    compound_types::UnlimitedVarArray,
+    xdr::{
+        LedgerEntryType, LedgerKey, Operation, OperationBody, OperationResult, OperationResultCode,
+        OperationResultTr, OperationType, TransactionResult, TransactionResultResult,
+    },
+    xdr_codec::XdrCodec,
+};
+
+// decode the XDR and check that encoding the result yields the same bytes
+fn decode_and_reencode<T: XdrCodec>(xdr: &[u8]) -> T {
+    let value = T::from_xdr(xdr).unwrap();
+    assert_eq!(value.to_xdr(), xdr);
+    value
+}
+
+#[test]
+fn unknown_operation_type() {
+    let operation_type: OperationType = decode_and_reencode(&[0, 0, 0, 99]);
+    assert_eq!(operation_type, OperationType::Unknown(99));
+
+    let operation_type: OperationType = decode_and_reencode(&[0, 0, 0, 1]);
+    assert_eq!(operation_type, OperationType::Payment);
+
+    // an operation without source account and with an unknown type
+    let operation: Operation = decode_and_reencode(&[0, 0, 0, 0, 0, 0, 0, 99]);
+    assert_eq!(operation.source_account, None);
+    assert_eq!(
+        operation.body,
+        OperationBody::Default(OperationType::Unknown(99))
+    );
+}
+
+#[test]
+fn unknown_operation_result_code() {
+    let result_code: OperationResultCode = decode_and_reencode(&(-100i32).to_be_bytes());
+    assert_eq!(result_code, OperationResultCode::Unknown(-100));
+
+    let operation_result: OperationResult = decode_and_reencode(&(-100i32).to_be_bytes());
+    assert_eq!(
+        operation_result,
+        OperationResult::Default(OperationResultCode::Unknown(-100))
+    );
+
+    // the inner result of an operation with an unknown type
+    let operation_result: OperationResult = decode_and_reencode(&[0, 0, 0, 0, 0, 0, 0, 99]);
+    assert_eq!(
+        operation_result,
+        OperationResult::OpInner(OperationResultTr::Default(OperationType::Unknown(99)))
+    );
+}
+
+#[test]
+fn transaction_result_with_unknown_operation_result_code() {
+    let mut xdr = Vec::new();
+    xdr.extend_from_slice(&100i64.to_be_bytes()); // fee charged
+    xdr.extend_from_slice(&(-1i32).to_be_bytes()); // txFAILED
+    xdr.extend_from_slice(&1u32.to_be_bytes()); // one operation result
+    xdr.extend_from_slice(&(-100i32).to_be_bytes()); // unknown operation result code
+    xdr.extend_from_slice(&0i32.to_be_bytes()); // ext
+
+    let transaction_result: TransactionResult = decode_and_reencode(&xdr);
+    assert_eq!(transaction_result.fee_charged, 100);
+    assert_eq!(
+        transaction_result.result,
+        TransactionResultResult::TxFailed(
+            UnlimitedVarArray::new(vec![OperationResult::Default(
+                OperationResultCode::Unknown(-100)
+            )])
+            .unwrap()
+        )
+    );
+}
+
+#[test]
+fn unknown_ledger_entry_type() {
+    let ledger_entry_type: LedgerEntryType = decode_and_reencode(&[0, 0, 0, 99]);
+    assert_eq!(ledger_entry_type, LedgerEntryType::Unknown(99));
+
+    let ledger_key: LedgerKey = decode_and_reencode(&[0, 0, 0, 99]);
+    assert_eq!(ledger_key, LedgerKey::Default(LedgerEntryType::Unknown(99)));
+}
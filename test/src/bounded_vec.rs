@@ -0,0 +1,38 @@
+//! Tests of the conversions between the compound types and `BoundedVec`
+
+use std::convert::TryFrom;
+
+use bounded_collections::{BoundedVec, ConstU32};
+use substrate_stellar_xdr::compound_types::{LimitedVarArray, LimitedVarOpaque};
+
+#[test]
+fn limited_var_opaque_conversions() {
+    let data = LimitedVarOpaque::<64>::new(vec![0xca, 0xfe]).unwrap();
+
+    let stored = BoundedVec::<u8, ConstU32<64>>::try_from(data.clone()).unwrap();
+    assert_eq!(&stored[..], &[0xca, 0xfe]);
+    assert_eq!(LimitedVarOpaque::<64>::try_from(stored).unwrap(), data);
+
+    let error = BoundedVec::<u8, ConstU32<1>>::try_from(data.clone()).unwrap_err();
+    assert_eq!(error.requested_length, 2);
+    assert_eq!(error.allowed_length, 1);
+
+    let stored = BoundedVec::<u8, ConstU32<64>>::try_from(data).unwrap();
+    assert!(LimitedVarOpaque::<1>::try_from(stored).is_err());
+}
+
+#[test]
+fn limited_var_array_conversions() {
+    let signers = LimitedVarArray::<u32, 20>::new(vec![1, 2, 3]).unwrap();
+
+    let stored = BoundedVec::<u32, ConstU32<20>>::try_from(signers.clone()).unwrap();
+    assert_eq!(&stored[..], &[1, 2, 3]);
+    assert_eq!(
+        LimitedVarArray::<u32, 20>::try_from(stored).unwrap(),
+        signers
+    );
+
+    assert!(BoundedVec::<u32, ConstU32<2>>::try_from(signers.clone()).is_err());
+    let stored = BoundedVec::<u32, ConstU32<20>>::try_from(signers).unwrap();
+    assert!(LimitedVarArray::<u32, 2>::try_from(stored).is_err());
+}
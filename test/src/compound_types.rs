@@ -0,0 +1,159 @@
+//! Tests of the API of the generic types in the module `compound_types`
+
+use std::convert::TryFrom;
+
+use substrate_stellar_xdr::{
+    compound_types::{
+        FromArray, InvalidTextError, LimitedLiteral, LimitedString, LimitedVarArray,
+        LimitedVarOpaque,
+    },
+    limited_string, limited_vec,
+    streams::{DecodeError, TextValidation},
+    xdr_codec::XdrCodec,
+};
+
+#[test]
+fn limited_string_text() {
+    let home_domain = LimitedString::<32>::try_from("stellar.org").unwrap();
+    assert_eq!(home_domain.as_str().unwrap(), "stellar.org");
+    assert_eq!(home_domain.to_string(), "stellar.org");
+    assert!(LimitedString::<4>::try_from("stellar.org").is_err());
+
+    let text = LimitedString::<32>::try_from("Grüße").unwrap();
+    assert_eq!(text.as_str().unwrap(), "Grüße");
+    assert!(matches!(
+        LimitedString::<32>::new_ascii("Grüße".as_bytes().to_vec()),
+        Err(InvalidTextError::InvalidByte { position: 2 })
+    ));
+    assert!(LimitedString::<32>::new_ascii(b"stellar.org".to_vec()).is_ok());
+
+    let binary = LimitedString::<32>::new(b"a\xffb\xe2\x82".to_vec()).unwrap();
+    assert!(binary.as_str().is_err());
+    assert_eq!(binary.to_string(), "a\\xffb\\xe2\\x82");
+}
+
+#[test]
+fn limited_string_text_validation() {
+    let text = LimitedString::<32>::try_from("Grüße").unwrap().to_xdr();
+    let binary = LimitedString::<32>::new(b"a\xffb".to_vec())
+        .unwrap()
+        .to_xdr();
+
+    assert!(LimitedString::<32>::from_xdr(&binary).is_ok());
+    assert!(
+        LimitedString::<32>::from_xdr_with_text_validation(&binary, TextValidation::Unchecked)
+            .is_ok()
+    );
+    assert!(
+        LimitedString::<32>::from_xdr_with_text_validation(&text, TextValidation::Utf8).is_ok()
+    );
+    assert!(matches!(
+        LimitedString::<32>::from_xdr_with_text_validation(&binary, TextValidation::Utf8),
+        Err(DecodeError::InvalidText { at_position: 4 })
+    ));
+    assert!(matches!(
+        LimitedString::<32>::from_xdr_with_text_validation(&text, TextValidation::Ascii),
+        Err(DecodeError::InvalidText { at_position: 4 })
+    ));
+}
+
+#[test]
+fn limited_var_array_bounds() {
+    let mut array = LimitedVarArray::<u32, 3>::new(vec![1]).unwrap();
+    assert_eq!(array.capacity_left(), 2);
+    array.push(2).unwrap();
+    array.push(3).unwrap();
+    assert_eq!(array.capacity_left(), 0);
+    assert!(array.push(4).is_err());
+    assert!(array.insert(0, 0).is_err());
+    assert!(array.extend(vec![4]).is_err());
+    assert_eq!(array.get_vec(), &vec![1, 2, 3]);
+
+    assert_eq!(array.pop(), Some(3));
+    array.insert(0, 0).unwrap();
+    assert_eq!(array.remove(1), 1);
+    array.extend(vec![4]).unwrap();
+    assert_eq!(array.into_vec(), vec![0, 2, 4]);
+
+    assert!(LimitedVarArray::<u32, 3>::try_from(vec![1, 2, 3]).is_ok());
+    assert!(LimitedVarArray::<u32, 3>::try_from(vec![1, 2, 3, 4]).is_err());
+}
+
+#[test]
+fn limited_var_array_collection() {
+    let mut array = LimitedVarArray::<u32, 10>::try_from(vec![1, 2, 3, 4, 5]).unwrap();
+    assert_eq!(array.len(), 5);
+    assert_eq!(array[1], 2);
+    assert_eq!(&array[1..3], &[2, 3]);
+    assert!(array.contains(&5));
+
+    array[0] = 10;
+    for element in array.iter_mut() {
+        *element += 1;
+    }
+    for element in &mut array {
+        *element *= 2;
+    }
+    assert_eq!((&array).into_iter().sum::<u32>(), 58);
+
+    array.retain(|element| element % 4 == 0);
+    array.truncate(1);
+    assert_eq!(array.into_iter().collect::<Vec<_>>(), vec![8]);
+}
+
+#[test]
+fn limited_var_array_extend() {
+    let mut array = LimitedVarArray::<u32, 5>::new(vec![1, 2]).unwrap();
+    let error = array.extend(3..).unwrap_err();
+    assert_eq!(error.requested_length, 6);
+    assert_eq!(array.get_vec(), &vec![1, 2]);
+
+    // the iterator is not consumed beyond the first element that does not fit
+    let mut items = 3..;
+    assert!(array.extend(items.by_ref()).is_err());
+    assert_eq!(items.next(), Some(7));
+
+    array.extend(3..6).unwrap();
+    assert_eq!(array.get_vec(), &vec![1, 2, 3, 4, 5]);
+    assert!(array.extend(6..7).is_err());
+    array.extend(Vec::new()).unwrap();
+}
+
+#[test]
+fn compile_time_bounded_constructors() {
+    let data = LimitedVarOpaque::<4>::from_array([0xca, 0xfe]);
+    assert_eq!(data, LimitedVarOpaque::new(vec![0xca, 0xfe]).unwrap());
+    let data: LimitedVarOpaque<2> = limited_vec![0xca, 0xfe];
+    assert_eq!(data.get_vec(), &vec![0xca, 0xfe]);
+
+    let signers: LimitedVarArray<u32, 20> = limited_vec![1, 2, 3];
+    assert_eq!(&signers[..], &[1, 2, 3]);
+    let empty: LimitedVarArray<u32, 20> = limited_vec![];
+    assert!(empty.is_empty());
+
+    let home_domain: LimitedString<11> = limited_string!("stellar.org");
+    assert_eq!(home_domain.as_str().unwrap(), "stellar.org");
+    let memo_text: LimitedString<28> = limited_string!("Grüße");
+    assert_eq!(memo_text.as_str().unwrap(), "Grüße");
+}
+
+const HOME_DOMAIN: LimitedLiteral<u8, 32> = LimitedLiteral::new(b"stellar.org");
+const DATA_VALUE: LimitedLiteral<u8, 2> = LimitedLiteral::new(&[0xca, 0xfe]);
+const WEIGHTS: LimitedLiteral<u32, 20> = LimitedLiteral::new(&[1, 2, 3]);
+
+#[test]
+fn const_literals() {
+    assert_eq!(HOME_DOMAIN.as_slice(), b"stellar.org");
+    assert_eq!(
+        LimitedString::from(HOME_DOMAIN),
+        LimitedString::try_from("stellar.org").unwrap()
+    );
+    assert_eq!(
+        LimitedVarOpaque::from(DATA_VALUE),
+        LimitedVarOpaque::new(vec![0xca, 0xfe]).unwrap()
+    );
+    assert_eq!(
+        LimitedVarArray::from(WEIGHTS),
+        LimitedVarArray::new(vec![1, 2, 3]).unwrap()
+    );
+}
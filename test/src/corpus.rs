@@ -0,0 +1,254 @@
+//! Conformance test against the golden vectors in the folder `corpus`
+
+use std::{collections::BTreeSet, ffi::OsStr, fs, path::Path};
+
+use substrate_stellar_xdr::{
+    dynamic::{decode_dynamic, discriminant_value, encode_dynamic, XdrValue},
+    streams::DecodeError,
+    xdr,
+    xdr_codec::XdrCodec,
+};
+
+/// A single golden vector of the corpus
+pub(crate) struct CorpusEntry {
+    pub(crate) location: String,
+    pub(crate) type_name: String,
+    pub(crate) base64_xdr: String,
+}
+
+pub(crate) fn read_corpus() -> Vec<CorpusEntry> {
+    let corpus_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus");
+    let mut file_paths: Vec<_> = fs::read_dir(&corpus_path)
+        .expect("Cannot read the corpus folder")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some(OsStr::new("txt")))
+        .collect();
+    file_paths.sort();
+
+    let mut entries = Vec::new();
+    for file_path in file_paths {
+        let content = fs::read_to_string(&file_path).unwrap();
+        for (line_index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let location = format!("{}:{}", file_path.display(), line_index + 1);
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(type_name), Some(base64_xdr), None) => entries.push(CorpusEntry {
+                    location,
+                    type_name: type_name.to_owned(),
+                    base64_xdr: base64_xdr.to_owned(),
+                }),
+                _ => panic!(
+                    "{}: expected an entry of the form `<type> <base64>`",
+                    location
+                ),
+            }
+        }
+    }
+
+    entries
+}
+
+fn round_trip<T: XdrCodec>(base64_xdr: &str) -> Result<Vec<u8>, DecodeError> {
+    let value = T::from_base64_xdr(base64_xdr)?;
+    Ok(value.to_base64_xdr())
+}
+
+/// Round trip through the generated type if the entry is of a root type, which is always generated
+macro_rules! typed_round_trip {
+    ($entry:expr, $($type_name:ident),*) => {
+        match &$entry.type_name[..] {
+            $(stringify!($type_name) => Some(round_trip::<xdr::$type_name>(&$entry.base64_xdr)),)*
+            _ => None,
+        }
+    };
+}
+
+/// Round trip through the dynamic decoder, which covers every type of the schema
+fn dynamic_round_trip(entry: &CorpusEntry) -> Result<Vec<u8>, String> {
+    let xdr =
+        base64::decode(&entry.base64_xdr).map_err(|error| format!("invalid base64: {}", error))?;
+    let value = decode_dynamic(&entry.type_name, &xdr)
+        .map_err(|error| format!("dynamic decoding failed: {:?}", error))?;
+    let encoded = encode_dynamic(&entry.type_name, &value)
+        .map_err(|error| format!("dynamic encoding failed: {:?}", error))?;
+    Ok(base64::encode(encoded).into_bytes())
+}
+
+fn check_entry(entry: &CorpusEntry) -> Result<(), String> {
+    let typed_result = typed_round_trip!(
+        entry,
+        TransactionEnvelope,
+        TransactionResult,
+        TransactionMeta,
+        EnvelopeType,
+        TransactionSignaturePayload
+    );
+    if let Some(result) = typed_result {
+        let encoded = result.map_err(|error| format!("decoding failed: {:?}", error))?;
+        if encoded != entry.base64_xdr.as_bytes() {
+            return Err("re-encoding yields different bytes".to_string());
+        }
+    }
+
+    if dynamic_round_trip(entry)? != entry.base64_xdr.as_bytes() {
+        return Err("dynamic re-encoding yields different bytes".to_string());
+    }
+    Ok(())
+}
+
+#[test]
+fn corpus_round_trip() {
+    let entries = read_corpus();
+    assert!(!entries.is_empty(), "The corpus is empty");
+
+    let failures: Vec<String> = entries
+        .iter()
+        .filter_map(|entry| {
+            check_entry(entry)
+                .err()
+                .map(|error| format!("{}: {}", entry.location, error))
+        })
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "Corpus entries failed:\n{}",
+        failures.join("\n")
+    );
+}
+
+/// Collect the names of all enum cases in `value`, including the discriminants of unions
+fn collect_enum_names(value: &XdrValue, names: &mut BTreeSet<&'static str>) {
+    match value {
+        XdrValue::Enum { name, .. } => {
+            names.insert(name);
+        }
+        XdrValue::Array(elements) => {
+            for element in elements {
+                collect_enum_names(element, names);
+            }
+        }
+        XdrValue::Optional(Some(value)) => collect_enum_names(value, names),
+        XdrValue::Struct(fields) => {
+            for (_, value) in fields {
+                collect_enum_names(value, names);
+            }
+        }
+        XdrValue::Union { discriminant, arm } => {
+            collect_enum_names(discriminant, names);
+            if let Some((_, value)) = arm {
+                collect_enum_names(value, names);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Decode all corpus entries of the type `type_name` dynamically
+fn decode_corpus_entries(type_name: &str) -> Vec<XdrValue> {
+    read_corpus()
+        .iter()
+        .filter(|entry| entry.type_name == type_name)
+        .map(|entry| decode_dynamic(type_name, base64::decode(&entry.base64_xdr).unwrap()).unwrap())
+        .collect()
+}
+
+fn missing_enum_names(type_name: &str, expected: &[&'static str]) -> Vec<&'static str> {
+    let mut names = BTreeSet::new();
+    for value in decode_corpus_entries(type_name) {
+        collect_enum_names(&value, &mut names);
+    }
+    expected
+        .iter()
+        .filter(|name| !names.contains(*name))
+        .cloned()
+        .collect()
+}
+
+/// The corpus needs to grow with the protocol: every operation, every result code and every meta
+/// version needs to occur in it
+#[test]
+fn corpus_coverage() {
+    let operation_types: Vec<_> = xdr::OperationType::ALL
+        .iter()
+        .map(|case| case.name())
+        .collect();
+    let missing_operation_types = missing_enum_names("TransactionEnvelope", &operation_types);
+    assert!(
+        missing_operation_types.is_empty(),
+        "No TransactionEnvelope in the corpus has the operations {:?}",
+        missing_operation_types
+    );
+
+    let result_codes: Vec<_> = xdr::TransactionResultCode::ALL
+        .iter()
+        .map(|case| case.name())
+        .chain(xdr::OperationResultCode::ALL.iter().map(|case| case.name()))
+        .collect();
+    let missing_result_codes = missing_enum_names("TransactionResult", &result_codes);
+    assert!(
+        missing_result_codes.is_empty(),
+        "No TransactionResult in the corpus has the result codes {:?}",
+        missing_result_codes
+    );
+
+    let meta_versions: BTreeSet<_> = decode_corpus_entries("TransactionMeta")
+        .iter()
+        .filter_map(|value| match value {
+            XdrValue::Union { discriminant, .. } => discriminant_value(discriminant),
+            _ => None,
+        })
+        .collect();
+    let missing_meta_versions: Vec<_> = xdr::type_metadata("TransactionMeta")
+        .unwrap()
+        .union_arms
+        .iter()
+        .map(|arm| arm.discriminant)
+        .filter(|version| !meta_versions.contains(version))
+        .collect();
+    assert!(
+        missing_meta_versions.is_empty(),
+        "No TransactionMeta in the corpus has the versions {:?}",
+        missing_meta_versions
+    );
+}
+
+#[test]
+fn corpus_transport_encodings() {
+    for entry in read_corpus()
+        .iter()
+        .filter(|entry| entry.type_name == "TransactionEnvelope")
+    {
+        let envelope = xdr::TransactionEnvelope::from_base64_xdr(&entry.base64_xdr).unwrap();
+        assert_eq!(envelope.to_base64_xdr_string(), entry.base64_xdr);
+
+        let hex_xdr = envelope.to_hex_xdr_string();
+        assert_eq!(
+            xdr::TransactionEnvelope::from_hex_xdr(&hex_xdr).unwrap(),
+            envelope
+        );
+        assert_eq!(
+            xdr::TransactionEnvelope::from_hex_xdr(hex_xdr.to_uppercase()).unwrap(),
+            envelope
+        );
+
+        let url_xdr = envelope.to_base64_url_xdr_string();
+        assert!(!url_xdr.contains(|character| "+/=".contains(character)));
+        assert_eq!(
+            xdr::TransactionEnvelope::from_base64_url_xdr(&url_xdr).unwrap(),
+            envelope,
+            "{}",
+            entry.location
+        );
+    }
+
+    assert!(matches!(
+        xdr::TransactionEnvelope::from_hex_xdr("0g"),
+        Err(DecodeError::InvalidHex)
+    ));
+}
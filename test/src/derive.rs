@@ -0,0 +1,92 @@
+//! Tests that types deriving `XdrCodec` use the same wire format as the generated types
+
+use substrate_stellar_xdr::{compound_types::LimitedString, xdr, xdr_codec::XdrCodec};
+
+#[derive(XdrCodec, Debug, Clone, PartialEq)]
+struct Fraction {
+    numerator: i32,
+    denominator: i32,
+}
+
+#[derive(XdrCodec, Debug, Clone, PartialEq)]
+struct Wrapper(u64, bool);
+
+#[derive(XdrCodec, Debug, Copy, Clone, PartialEq)]
+enum BridgeMemoType {
+    None = 0,
+    Text = 1,
+    Id = 2,
+}
+
+#[derive(XdrCodec, Debug, Clone, PartialEq)]
+#[xdr(switch = "xdr::MemoType")]
+enum BridgeMemo {
+    #[xdr(case = "xdr::MemoType::MemoNone")]
+    None,
+    #[xdr(case = "xdr::MemoType::MemoText")]
+    Text(LimitedString<28>),
+    #[xdr(case = "xdr::MemoType::MemoId")]
+    Id(u64),
+    #[xdr(default)]
+    Other(xdr::MemoType),
+}
+
+#[derive(XdrCodec, Debug, Clone, PartialEq)]
+enum Extension {
+    #[xdr(case = 0)]
+    V0,
+    #[xdr(case = 1)]
+    V1(Fraction),
+}
+
+fn assert_same_encoding<A: XdrCodec + PartialEq + core::fmt::Debug, B: XdrCodec>(
+    derived: A,
+    generated: B,
+) {
+    let encoded = derived.to_xdr();
+    assert_eq!(encoded, generated.to_xdr());
+    assert_eq!(A::from_xdr(&encoded).unwrap(), derived);
+}
+
+#[test]
+fn derived_struct() {
+    let price = xdr::Price { n: 3, d: -7 };
+    assert_same_encoding(
+        Fraction {
+            numerator: 3,
+            denominator: -7,
+        },
+        price,
+    );
+
+    let wrapper = Wrapper(42, true);
+    assert_eq!(Wrapper::from_xdr(wrapper.to_xdr()).unwrap(), wrapper);
+}
+
+#[test]
+fn derived_enum() {
+    assert_same_encoding(BridgeMemoType::None, xdr::MemoType::MemoNone);
+    assert_same_encoding(BridgeMemoType::Text, xdr::MemoType::MemoText);
+    assert_same_encoding(BridgeMemoType::Id, xdr::MemoType::MemoId);
+    assert!(BridgeMemoType::from_xdr(xdr::MemoType::MemoHash.to_xdr()).is_err());
+}
+
+#[test]
+fn derived_union() {
+    let text = LimitedString::new(b"bridge".to_vec()).unwrap();
+    assert_same_encoding(BridgeMemo::None, xdr::Memo::MemoNone);
+    assert_same_encoding(BridgeMemo::Text(text.clone()), xdr::Memo::MemoText(text));
+    assert_same_encoding(BridgeMemo::Id(5), xdr::Memo::MemoId(5));
+    assert_same_encoding(
+        BridgeMemo::Other(xdr::MemoType::MemoHash),
+        xdr::MemoType::MemoHash,
+    );
+
+    let extension = Extension::V1(Fraction {
+        numerator: 1,
+        denominator: 2,
+    });
+    assert_eq!(Extension::from_xdr(extension.to_xdr()).unwrap(), extension);
+    assert_eq!(Extension::V0.to_xdr(), 0i32.to_xdr());
+    assert!(Extension::from_xdr(2i32.to_xdr()).is_err());
+}
@@ -0,0 +1,46 @@
+//! Tests of the schema-driven decoder of the feature `dynamic`
+
+use substrate_stellar_xdr::{
+    dynamic::{decode_dynamic, guess_types, DynamicError, MAX_DEPTH},
+    streams::DecodeError,
+};
+
+/// Binary XDR of a `ClaimPredicate` that negates `CLAIM_PREDICATE_UNCONDITIONAL` `depth` times
+fn nested_claim_predicate(depth: usize) -> Vec<u8> {
+    let mut xdr = Vec::new();
+    for _ in 0..depth {
+        // CLAIM_PREDICATE_NOT followed by a present `notPredicate`
+        xdr.extend_from_slice(&[0, 0, 0, 3, 0, 0, 0, 1]);
+    }
+    xdr.extend_from_slice(&[0, 0, 0, 0]);
+    xdr
+}
+
+#[test]
+fn decode_nested_values() {
+    assert!(decode_dynamic("ClaimPredicate", nested_claim_predicate(4)).is_ok());
+    // the type of the innermost predicate, `ClaimPredicateType`, is one definition deeper
+    assert!(decode_dynamic("ClaimPredicate", nested_claim_predicate(MAX_DEPTH - 1)).is_ok());
+}
+
+#[test]
+fn reject_too_deeply_nested_values() {
+    assert!(matches!(
+        decode_dynamic("ClaimPredicate", nested_claim_predicate(MAX_DEPTH)),
+        Err(DynamicError::Decode(
+            DecodeError::MaximumDepthExceeded { .. }
+        ))
+    ));
+
+    // without the limit, every 8 bytes of this input would add stack frames until the stack overflows
+    let xdr = nested_claim_predicate(100_000);
+    assert!(matches!(
+        decode_dynamic("ClaimPredicate", &xdr),
+        Err(DynamicError::Decode(
+            DecodeError::MaximumDepthExceeded { .. }
+        ))
+    ));
+    assert!(guess_types(&xdr)
+        .iter()
+        .all(|guess| guess.type_name != "ClaimPredicate"));
+}
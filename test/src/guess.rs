@@ -0,0 +1,56 @@
+//! Tests of guessing the types of the golden vectors in the folder `corpus`
+
+use substrate_stellar_xdr::{dynamic::guess_types, xdr, xdr_codec::XdrCodec};
+
+use crate::corpus::{read_corpus, CorpusEntry};
+
+fn binary_xdr(entry: &CorpusEntry) -> Option<Vec<u8>> {
+    let base64_xdr = &entry.base64_xdr;
+    let xdr = match &entry.type_name[..] {
+        "TransactionEnvelope" => xdr::TransactionEnvelope::from_base64_xdr(base64_xdr)
+            .unwrap()
+            .to_xdr(),
+        "TransactionResult" => xdr::TransactionResult::from_base64_xdr(base64_xdr)
+            .unwrap()
+            .to_xdr(),
+        "TransactionMeta" => xdr::TransactionMeta::from_base64_xdr(base64_xdr)
+            .unwrap()
+            .to_xdr(),
+        _ => return None,
+    };
+    Some(xdr)
+}
+
+#[test]
+fn guess_corpus_types() {
+    for entry in read_corpus() {
+        let xdr = match binary_xdr(&entry) {
+            Some(xdr) => xdr,
+            None => continue,
+        };
+
+        let guesses = guess_types(&xdr);
+        let type_names: Vec<_> = guesses.iter().map(|guess| guess.type_name).collect();
+        assert_eq!(
+            type_names.first(),
+            Some(&&entry.type_name[..]),
+            "{}: guessed {:?}",
+            entry.location,
+            type_names
+        );
+    }
+}
+
+#[test]
+fn guess_truncated_data() {
+    let entry = read_corpus()
+        .into_iter()
+        .find(|entry| entry.type_name == "TransactionEnvelope")
+        .unwrap();
+    let xdr = binary_xdr(&entry).unwrap();
+
+    let guesses = guess_types(&xdr[..xdr.len() - 4]);
+    assert!(guesses
+        .iter()
+        .all(|guess| guess.type_name != "TransactionEnvelope"));
+}
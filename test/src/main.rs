@@ -1,5 +1,24 @@
 use substrate_stellar_xdr::{xdr, xdr_codec::XdrCodec};
 
+#[cfg(test)]
+mod bounded_vec;
+#[cfg(test)]
+mod compound_types;
+#[cfg(test)]
+mod corpus;
+#[cfg(test)]
+mod derive;
+#[cfg(test)]
+mod dynamic;
+#[cfg(test)]
+mod guess;
+#[cfg(test)]
+mod open_enums;
+#[cfg(test)]
+mod streams;
+#[cfg(test)]
+mod txrep;
+
 const ENVELOPE: &[u8; 408] = b"AAAAAgAAAAC9xFYU1gQJeH4apEfzJkMCsW5DL4GEWRpyVjQHOlWVzgAAAZA\
 CGsQoAAQytgAAAAAAAAAAAAAAAgAAAAAAAAADAAAAAVhMUEcAAAAAxxJMrxQQOx9raxDm3\
 lINsLvksi7tj1BCQXzWTtqigbgAAAAAAAAAAAbK5N8CprKDAExLQAAAAAAAAAAAAAAAAAA\
@@ -0,0 +1,71 @@
+//! Tests of decoding and encoding undefined values of open enums with the dynamic schema
+
+use substrate_stellar_xdr::{
+    dynamic::{
+        decode_definition, decode_dynamic, encode_definition, SchemaDefinition, XdrValue,
+        UNKNOWN_ENUM_CASE,
+    },
+    streams::{ReadStream, WriteStream},
+    type_metadata::EnumCaseMetadata,
+};
+
+const CASES: &[EnumCaseMetadata] = &[
+    EnumCaseMetadata {
+        name: "FIRST",
+        value: 0,
+    },
+    EnumCaseMetadata {
+        name: "SECOND",
+        value: 1,
+    },
+];
+
+const OPEN: SchemaDefinition = SchemaDefinition::Enum {
+    cases: CASES,
+    open: true,
+};
+
+const CLOSED: SchemaDefinition = SchemaDefinition::Enum {
+    cases: CASES,
+    open: false,
+};
+
+fn decode(definition: &SchemaDefinition, value: i32) -> Option<XdrValue> {
+    decode_definition(definition, &mut ReadStream::new(value.to_be_bytes())).ok()
+}
+
+fn encode(definition: &SchemaDefinition, value: &XdrValue) -> Option<Vec<u8>> {
+    let mut write_stream = WriteStream::new();
+    encode_definition(definition, value, &mut write_stream).ok()?;
+    Some(write_stream.get_result())
+}
+
+#[test]
+fn open_enum_preserves_unknown_values() {
+    let unknown = XdrValue::Enum {
+        name: UNKNOWN_ENUM_CASE,
+        value: 7,
+    };
+    assert_eq!(decode(&OPEN, 7), Some(unknown.clone()));
+    assert_eq!(encode(&OPEN, &unknown), Some(7i32.to_be_bytes().to_vec()));
+
+    let second = XdrValue::Enum {
+        name: "SECOND",
+        value: 1,
+    };
+    assert_eq!(decode(&OPEN, 1), Some(second.clone()));
+    assert_eq!(encode(&OPEN, &second), Some(1i32.to_be_bytes().to_vec()));
+}
+
+#[test]
+fn closed_enum_rejects_unknown_values() {
+    let unknown = XdrValue::Enum {
+        name: UNKNOWN_ENUM_CASE,
+        value: 7,
+    };
+    assert_eq!(decode(&CLOSED, 7), None);
+    assert_eq!(encode(&CLOSED, &unknown), None);
+
+    // enums are closed unless they are listed in `OPEN_ENUMS` when generating
+    assert!(decode_dynamic("OperationType", 99i32.to_be_bytes()).is_err());
+}
@@ -0,0 +1,210 @@
+//! Tests of encoding and decoding via `ReadStream` and `WriteStream`
+
+use base64::{Config, STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+use substrate_stellar_xdr::{
+    compound_types::UnlimitedVarOpaque,
+    dynamic::decode_dynamic,
+    streams::{DecodeError, ReadStream, WriteStream},
+    xdr,
+    xdr_codec::XdrCodec,
+};
+
+use crate::corpus::read_corpus;
+
+#[test]
+fn decode_truncated_integers() {
+    assert!(matches!(
+        u64::from_xdr([0, 0, 0]),
+        Err(DecodeError::SuddenEnd {
+            actual_length: 3,
+            expected_length: 8
+        })
+    ));
+    assert!(matches!(
+        i32::from_xdr([]),
+        Err(DecodeError::SuddenEnd {
+            actual_length: 0,
+            expected_length: 4
+        })
+    ));
+}
+
+#[test]
+fn decode_truncated_input() {
+    let entry = read_corpus()
+        .into_iter()
+        .find(|entry| entry.type_name == "TransactionEnvelope")
+        .unwrap();
+    let xdr = xdr::TransactionEnvelope::from_base64_xdr(&entry.base64_xdr)
+        .unwrap()
+        .to_xdr();
+
+    // every prefix ends within a value, so that decoding needs to fail instead of panicking
+    for length in 0..xdr.len() {
+        let truncated = &xdr[..length];
+        assert!(
+            matches!(
+                xdr::TransactionEnvelope::from_xdr(truncated),
+                Err(DecodeError::SuddenEnd { .. })
+            ),
+            "{}",
+            length
+        );
+        assert!(decode_dynamic("TransactionEnvelope", truncated).is_err());
+    }
+}
+
+const CONFIGS: [Config; 4] = [STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD];
+
+/// Bytes that cover all base64 characters, including `+` and `/` resp. `-` and `_`
+fn payload(length: usize) -> Vec<u8> {
+    (0..length).map(|index| (index * 167 + 13) as u8).collect()
+}
+
+#[test]
+fn encode_base64_stream() {
+    for length in 0..200 {
+        let payload = payload(length);
+        // writes of different lengths leave different numbers of bytes pending
+        let write = |stream: &mut WriteStream| {
+            stream.write_next_u32(length as u32);
+            for piece in payload.chunks(1 + length % 7) {
+                stream.write_next_binary_data(piece);
+            }
+            stream.write_next_i64(-1);
+        };
+
+        let mut binary_stream = WriteStream::new();
+        write(&mut binary_stream);
+        let binary = binary_stream.get_result();
+
+        for config in CONFIGS {
+            let mut base64_stream = WriteStream::new_base64(config);
+            write(&mut base64_stream);
+            assert_eq!(
+                base64_stream.get_result(),
+                base64::encode_config(&binary, config).into_bytes(),
+                "{}",
+                length
+            );
+        }
+    }
+}
+
+#[test]
+fn decode_base64_stream() {
+    for length in 0..200 {
+        let payload = payload(length);
+        let aligned_length = length / 4 * 4;
+
+        for config in CONFIGS {
+            let encoded = base64::encode_config(&payload, config);
+
+            // a single read spans several chunks of `BASE64_GROUPS_PER_CHUNK` groups
+            let mut stream = ReadStream::new_base64(&encoded, config).unwrap();
+            assert_eq!(stream.no_of_bytes_left_to_read(), length as isize);
+            let mut buffer = vec![0; aligned_length];
+            stream.read_next_binary_data_into(&mut buffer).unwrap();
+            assert_eq!(buffer, payload[..aligned_length], "{}", length);
+            assert_eq!(stream.no_of_bytes_left_to_read(), (length % 4) as isize);
+
+            // reads that start within a group of 3 bytes and end within a chunk
+            let mut stream = ReadStream::new_base64(&encoded, config).unwrap();
+            for (index, expected) in payload.chunks_exact(4).enumerate() {
+                let value = stream.read_next_u32().unwrap();
+                assert_eq!(value.to_be_bytes(), expected, "{} {}", length, index);
+                assert_eq!(
+                    stream.no_of_bytes_left_to_read(),
+                    (length - 4 * index - 4) as isize
+                );
+            }
+            assert!(matches!(
+                stream.read_next_u32(),
+                Err(DecodeError::SuddenEnd { .. })
+            ));
+        }
+    }
+}
+
+#[test]
+fn decode_unpadded_url_safe_base64() {
+    let opaque = UnlimitedVarOpaque::new(payload(100)).unwrap();
+    let encoded = base64::encode_config(opaque.to_xdr(), URL_SAFE_NO_PAD);
+    assert!(encoded.contains('-') && encoded.contains('_') && !encoded.ends_with('='));
+
+    assert_eq!(opaque.to_base64_url_xdr(), encoded.as_bytes());
+    assert_eq!(
+        UnlimitedVarOpaque::from_base64_url_xdr(&encoded).unwrap(),
+        opaque
+    );
+    assert!(matches!(
+        UnlimitedVarOpaque::from_base64_xdr(&encoded),
+        Err(DecodeError::InvalidBase64)
+    ));
+}
+
+#[test]
+fn decode_base64_with_padding_in_the_middle() {
+    // the padding ends a group within a chunk
+    let encoded = format!("AQ=={}", base64::encode([1; 6]));
+    let mut stream = ReadStream::new_base64(&encoded, STANDARD).unwrap();
+    assert!(matches!(
+        stream.read_next_u64(),
+        Err(DecodeError::InvalidBase64)
+    ));
+
+    // the padding ends a chunk of `BASE64_GROUPS_PER_CHUNK` groups
+    let encoded = format!("{}{}", base64::encode([1; 47]), base64::encode([1; 3]));
+    let mut stream = ReadStream::new_base64(&encoded, STANDARD).unwrap();
+    let mut buffer = [0; 48];
+    assert!(matches!(
+        stream.read_next_binary_data_into(&mut buffer),
+        Err(DecodeError::InvalidBase64)
+    ));
+}
+
+#[test]
+fn decode_invalid_base64() {
+    for invalid_length in ["A", "AAAAA", "AAAA=", "AAAAA=", "AAAAAA="] {
+        assert!(
+            matches!(
+                ReadStream::new_base64(invalid_length, STANDARD),
+                Err(DecodeError::InvalidBase64)
+            ),
+            "{}",
+            invalid_length
+        );
+    }
+
+    for invalid_characters in ["AAAA*A==", "AA AAA==", "AAAAA-==", "AAAAAA=A", "========"] {
+        assert!(
+            matches!(
+                u32::from_base64_xdr(invalid_characters),
+                Err(DecodeError::InvalidBase64)
+            ),
+            "{}",
+            invalid_characters
+        );
+    }
+}
+
+#[test]
+fn decode_malformed_base64_without_panic() {
+    let inputs: [&[u8]; 8] = [
+        b"",
+        b"=",
+        b"==",
+        b"AAAAAA==",
+        b"AAAAAAA=AAAAAAAA",
+        b"AAAAAQ==AAAAAAAA",
+        b"\xff\x80AA",
+        b"AAAAYQ\xc3\xa4=",
+    ];
+    for input in inputs {
+        for config in CONFIGS {
+            if let Ok(mut stream) = ReadStream::new_base64(input, config) {
+                let _ = UnlimitedVarOpaque::from_xdr_buffered(&mut stream);
+            }
+        }
+    }
+}
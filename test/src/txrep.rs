@@ -0,0 +1,209 @@
+//! Tests of the SEP-0011 Txrep of the envelopes in the folder `corpus`
+//!
+//! Signed payload signers (`P...` strkeys) only exist from protocol 19 on, so the pinned XDR
+//! version cannot represent them and they are not covered here.
+
+use substrate_stellar_xdr::{txrep::TxrepError, xdr, xdr_codec::XdrCodec};
+
+use crate::corpus::read_corpus;
+
+#[test]
+fn txrep_round_trip() {
+    let envelopes: Vec<_> = read_corpus()
+        .into_iter()
+        .filter(|entry| entry.type_name == "TransactionEnvelope")
+        .collect();
+    assert!(!envelopes.is_empty(), "The corpus contains no envelopes");
+
+    for entry in envelopes {
+        let envelope = xdr::TransactionEnvelope::from_base64_xdr(&entry.base64_xdr).unwrap();
+        let txrep = envelope.to_txrep().unwrap();
+        let parsed = xdr::TransactionEnvelope::from_txrep(&txrep)
+            .unwrap_or_else(|error| panic!("{}: {}\n{}", entry.location, error, txrep));
+        assert_eq!(
+            parsed.to_base64_xdr(),
+            entry.base64_xdr.as_bytes(),
+            "{}",
+            entry.location
+        );
+    }
+}
+
+#[test]
+fn txrep_representation() {
+    let entry = read_corpus()
+        .into_iter()
+        .find(|entry| entry.type_name == "TransactionEnvelope")
+        .unwrap();
+    let envelope = xdr::TransactionEnvelope::from_base64_xdr(&entry.base64_xdr).unwrap();
+    let txrep = envelope.to_txrep().unwrap();
+
+    assert!(txrep.starts_with(
+        "type: ENVELOPE_TYPE_TX\n\
+         tx.sourceAccount: GC64IVQU2YCAS6D6DKSEP4ZGIMBLC3SDF6AYIWI2OJLDIBZ2KWK45B2R\n\
+         tx.fee: 400\n\
+         tx.seqNum: 151649213548343990\n"
+    ));
+
+    // comments after values are ignored
+    let commented = txrep.replace("tx.fee: 400\n", "tx.fee: 400 (0.00004 XLM)\n");
+    assert_eq!(
+        xdr::TransactionEnvelope::from_txrep(&commented).unwrap(),
+        envelope
+    );
+
+    let duplicated = format!("{}tx.fee: 400\n", txrep);
+    assert!(matches!(
+        xdr::TransactionEnvelope::from_txrep(&duplicated),
+        Err(TxrepError::DuplicateKey(key)) if key == "tx.fee"
+    ));
+
+    let missing = txrep.replace("tx.fee: 400\n", "");
+    assert!(matches!(
+        xdr::TransactionEnvelope::from_txrep(&missing),
+        Err(TxrepError::MissingKey(key)) if key == "tx.fee"
+    ));
+}
+
+/// Returns the values of all lines of the Txrep of the corpus envelopes
+fn corpus_txrep_lines() -> Vec<String> {
+    read_corpus()
+        .into_iter()
+        .filter(|entry| entry.type_name == "TransactionEnvelope")
+        .flat_map(|entry| {
+            let envelope = xdr::TransactionEnvelope::from_base64_xdr(&entry.base64_xdr).unwrap();
+            let txrep = envelope.to_txrep().unwrap();
+            txrep.lines().map(str::to_owned).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[test]
+fn txrep_corpus_coverage() {
+    let lines = corpus_txrep_lines();
+    let has_line_ending = |suffix: &str| lines.iter().any(|line| line.ends_with(suffix));
+    let values: Vec<&str> = lines
+        .iter()
+        .map(|line| &line[line.find(": ").unwrap() + 2..])
+        .collect();
+    let has_strkey = |prefix: char, length: usize| {
+        values
+            .iter()
+            .any(|value| value.starts_with(prefix) && value.len() == length)
+    };
+
+    assert!(has_line_ending(": ENVELOPE_TYPE_TX_V0"));
+    assert!(has_line_ending(": ENVELOPE_TYPE_TX_FEE_BUMP"));
+    for memo_type in &["MEMO_TEXT", "MEMO_ID", "MEMO_HASH", "MEMO_RETURN"] {
+        assert!(has_line_ending(&format!(".memo.type: {}", memo_type)));
+    }
+    assert!(has_line_ending(".timeBounds._present: true"));
+
+    // muxed accounts and signer keys are represented as strkeys, not field by field
+    assert!(has_strkey('M', 69));
+    assert!(has_strkey('T', 56));
+    assert!(has_strkey('X', 56));
+    assert!(values
+        .iter()
+        .any(|value| value.starts_with("LONGASSET12:G")));
+    assert!(values
+        .iter()
+        .any(|value| value.starts_with('"') && value.contains("\\x")));
+}
+
+#[test]
+fn txrep_exact_representation() {
+    // a muxed source account, time bounds, a text memo with escaped characters, an alphanum12
+    // asset and hash(x) and pre-authorized transaction signers
+    let base64_xdr = "AAAAAgAAAQAAAAAAAAAABxERERERERERERERERERERERERERERERERERERERERERAAAAyAAAAAAAAATSAAAAAQAAAAAAAAAAAAAAAGVT8QAAAAABAAAABmEiYlxjCgAAAAAAAwAAAAEAAAAAIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIiIAAAABAAAAADMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzAAAAAkxPTkdDT0RFAAAAAAAAAABERERERERERERERERERERERERERERERERERERERERERAAAAAAAmJaAAAAAAAAAAAUAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAEAAAACVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVUAAAABAAAAAAAAAAUAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAEAAAABZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmYAAAACAAAAAAAAAAGqu8zdAAAAQAEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=";
+    let envelope = xdr::TransactionEnvelope::from_base64_xdr(base64_xdr).unwrap();
+    let txrep = envelope.to_txrep().unwrap();
+
+    let set_options = |index: usize, signer: &str, weight: u32| {
+        let prefix = format!("tx.operations[{}]", index);
+        let mut lines = format!(
+            "{0}.sourceAccount._present: false\n\
+             {0}.body.type: SET_OPTIONS\n",
+            prefix
+        );
+        for field in &[
+            "inflationDest",
+            "clearFlags",
+            "setFlags",
+            "masterWeight",
+            "lowThreshold",
+            "medThreshold",
+            "highThreshold",
+            "homeDomain",
+        ] {
+            lines += &format!("{}.body.setOptionsOp.{}._present: false\n", prefix, field);
+        }
+        lines
+            + &format!(
+                "{0}.body.setOptionsOp.signer._present: true\n\
+                 {0}.body.setOptionsOp.signer.key: {1}\n\
+                 {0}.body.setOptionsOp.signer.weight: {2}\n",
+                prefix, signer, weight
+            )
+    };
+
+    let expected = String::from(
+        "type: ENVELOPE_TYPE_TX\n\
+         tx.sourceAccount: MAIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCAAAAAAAAAAAA74YM\n\
+         tx.fee: 200\n\
+         tx.seqNum: 1234\n\
+         tx.timeBounds._present: true\n\
+         tx.timeBounds.minTime: 0\n\
+         tx.timeBounds.maxTime: 1700000000\n\
+         tx.memo.type: MEMO_TEXT\n\
+         tx.memo.text: \"a\\\"b\\\\c\\x0a\"\n\
+         tx.operations.len: 3\n\
+         tx.operations[0].sourceAccount._present: true\n\
+         tx.operations[0].sourceAccount: GARCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCFRVX\n\
+         tx.operations[0].body.type: PAYMENT\n\
+         tx.operations[0].body.paymentOp.destination: GAZTGMZTGMZTGMZTGMZTGMZTGMZTGMZTGMZTGMZTGMZTGMZTGMZTHCM6\n\
+         tx.operations[0].body.paymentOp.asset: LONGCODE:GBCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIZCA\n\
+         tx.operations[0].body.paymentOp.amount: 10000000\n",
+    ) + &set_options(1, "XBKVKVKVKVKVKVKVKVKVKVKVKVKVKVKVKVKVKVKVKVKVKVKVKVKVKTMB", 1)
+        + &set_options(2, "TBTGMZTGMZTGMZTGMZTGMZTGMZTGMZTGMZTGMZTGMZTGMZTGMZTGMDCD", 2)
+        + "tx.ext.v: 0\n\
+           signatures.len: 1\n\
+           signatures[0].hint: aabbccdd\n\
+           signatures[0].signature: "
+        + &"01".repeat(64)
+        + "\n";
+    assert_eq!(txrep, expected);
+
+    assert_eq!(
+        xdr::TransactionEnvelope::from_txrep(&expected).unwrap(),
+        envelope
+    );
+}
+
+#[test]
+fn txrep_invalid_strkeys() {
+    let entry = read_corpus()
+        .into_iter()
+        .find(|entry| entry.type_name == "TransactionEnvelope")
+        .unwrap();
+    let envelope = xdr::TransactionEnvelope::from_base64_xdr(&entry.base64_xdr).unwrap();
+    let txrep = envelope.to_txrep().unwrap();
+    let source_account = "GC64IVQU2YCAS6D6DKSEP4ZGIMBLC3SDF6AYIWI2OJLDIBZ2KWK45B2R";
+
+    // wrong checksum, wrong version byte and a truncated key
+    for invalid in &[
+        "GC64IVQU2YCAS6D6DKSEP4ZGIMBLC3SDF6AYIWI2OJLDIBZ2KWK45B2S",
+        "TC64IVQU2YCAS6D6DKSEP4ZGIMBLC3SDF6AYIWI2OJLDIBZ2KWK45B2R",
+        "GC64IVQU2YCAS6D6DKSEP4ZGIMBLC3SDF6AYIWI2OJLDIBZ2KWK45",
+    ] {
+        let changed = txrep.replace(source_account, invalid);
+        assert!(
+            matches!(
+                xdr::TransactionEnvelope::from_txrep(&changed),
+                Err(TxrepError::InvalidValue(key)) if key == "tx.sourceAccount"
+            ),
+            "{}",
+            invalid
+        );
+    }
+}